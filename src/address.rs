@@ -1,6 +1,12 @@
 //! Device Address
 
+use crate::error::Error;
+use crate::MCP9808;
+use embedded_hal::i2c::{ErrorKind, I2c, SevenBitAddress};
+
 const DEFAULT_ADDRESS: u8 = 0b1_1000;
+/// bit 2-0: A2, A1, A0
+const MASK_PINS: u8 = 0b111;
 
 /// I2C device address
 ///
@@ -48,3 +54,152 @@ impl PartialEq for SlaveAddress {
         lhs == rhs
     }
 }
+
+/// Address outside of 0x18 - 0x1F
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidAddress(pub u8);
+
+impl TryFrom<u8> for SlaveAddress {
+    type Error = InvalidAddress;
+
+    fn try_from(addr: u8) -> Result<Self, Self::Error> {
+        if addr & !MASK_PINS != DEFAULT_ADDRESS {
+            return Err(InvalidAddress(addr));
+        }
+        if addr == DEFAULT_ADDRESS {
+            return Ok(SlaveAddress::Default);
+        }
+        Ok(SlaveAddress::Alternative {
+            a2: addr & 0b100 != 0,
+            a1: addr & 0b010 != 0,
+            a0: addr & 0b001 != 0,
+        })
+    }
+}
+
+impl SlaveAddress {
+    /// all eight addresses, in ascending order
+    pub fn all() -> impl Iterator<Item = SlaveAddress> {
+        (DEFAULT_ADDRESS..=DEFAULT_ADDRESS | MASK_PINS).filter_map(|addr| addr.try_into().ok())
+    }
+}
+
+/// Addresses an MCP9808 answered at, see `scan()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Found([bool; 8]);
+
+impl Found {
+    pub(crate) fn insert(&mut self, addr: SlaveAddress) {
+        if let Some(found) = self.0.get_mut((u8::from(addr) & MASK_PINS) as usize) {
+            *found = true;
+        }
+    }
+
+    pub fn contains(&self, addr: SlaveAddress) -> bool {
+        self.0
+            .get((u8::from(addr) & MASK_PINS) as usize)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// found addresses, in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = SlaveAddress> + '_ {
+        SlaveAddress::all().filter(|addr| self.contains(*addr))
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// whether `err` means nothing answered, or something else than an MCP9808
+pub(crate) fn is_absent<E: embedded_hal::i2c::Error>(err: &Error<E>) -> bool {
+    match err {
+        Error::I2c(e) => matches!(e.kind(), ErrorKind::NoAcknowledge(_)),
+        Error::WrongManufacturer(_) | Error::WrongDevice { .. } => true,
+        _ => false,
+    }
+}
+
+/// Finds all MCP9808 on the bus by checking manufacturer and device ID at 0x18 - 0x1F.
+///
+/// Addresses that do not acknowledge or report other IDs are skipped, other bus errors abort the scan.
+pub fn scan<I2C>(i2c: &mut I2C) -> Result<Found, Error<I2C::Error>>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    let mut found = Found::default();
+    let mut dev = MCP9808::new(i2c);
+    for addr in SlaveAddress::all() {
+        dev.set_address(addr);
+        match dev.verify_identity() {
+            Ok(()) => found.insert(addr),
+            Err(err) if is_absent(&err) => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal::i2c::NoAcknowledgeSource;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    #[test]
+    fn try_from() {
+        assert_eq!(SlaveAddress::try_from(0x18), Ok(SlaveAddress::Default));
+        assert_eq!(
+            SlaveAddress::try_from(0x1d),
+            Ok(SlaveAddress::Alternative {
+                a2: true,
+                a1: false,
+                a0: true
+            })
+        );
+        for addr in SlaveAddress::all() {
+            assert_eq!(SlaveAddress::try_from(u8::from(addr)), Ok(addr));
+        }
+        assert_eq!(SlaveAddress::try_from(0x17), Err(InvalidAddress(0x17)));
+        assert_eq!(SlaveAddress::try_from(0x20), Err(InvalidAddress(0x20)));
+        assert_eq!(SlaveAddress::try_from(0x98), Err(InvalidAddress(0x98)));
+    }
+
+    #[test]
+    fn scan_bus() {
+        let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+        let absent =
+            |addr| Transaction::write_read(addr, vec![0b0110], vec![0, 0]).with_error(nack);
+        let expectations = [
+            absent(0x18),
+            Transaction::write_read(0x19, vec![0b0110], vec![0x00, 0x54]),
+            Transaction::write_read(0x19, vec![0b0111], vec![0x04, 0x00]),
+            absent(0x1a),
+            // some other device
+            Transaction::write_read(0x1b, vec![0b0110], vec![0x12, 0x34]),
+            absent(0x1c),
+            absent(0x1d),
+            absent(0x1e),
+            Transaction::write_read(0x1f, vec![0b0110], vec![0x00, 0x54]),
+            Transaction::write_read(0x1f, vec![0b0111], vec![0x04, 0x01]),
+        ];
+        let mut i2c = Mock::new(&expectations);
+
+        let found = scan(&mut i2c).unwrap();
+        assert_eq!(found.len(), 2);
+        assert!(found.contains(SlaveAddress::try_from(0x19).unwrap()));
+        assert_eq!(found.iter().map(u8::from).collect::<Vec<_>>(), [0x19, 0x1f]);
+
+        i2c.done();
+
+        let expectations = [absent(0x18).with_error(ErrorKind::Bus)];
+        let mut i2c = Mock::new(&expectations);
+        assert!(matches!(scan(&mut i2c), Err(Error::I2c(ErrorKind::Bus))));
+        i2c.done();
+    }
+}
@@ -0,0 +1,608 @@
+//! Alert limits
+
+use crate::celsius::Celsius;
+use crate::error::ValueError;
+use crate::prelude::Read;
+use crate::reg::Register;
+use crate::reg_conf::{
+    AlertControl, AlertMode, AlertPolarity, AlertSelect, AlertStatus, Configuration,
+    ConfigurationRegister, Hysteresis, InterruptClear, ShutdownMode,
+};
+use crate::reg_res::ResolutionVal;
+use crate::reg_temp::{Temperature, TemperatureRegister};
+use crate::reg_temp_alert_crit::{self, CriticalLimitRegister};
+use crate::reg_temp_alert_lower::{self, LowerLimitRegister};
+use crate::reg_temp_alert_upper::{self, UpperLimitRegister};
+use crate::reg_temp_generic::{
+    encode_limit, ReadableTempRegister, WritableTempRegister, MASK_LIMIT_FRACT,
+};
+
+/// One of the alert limit registers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    /// tLOWER
+    Lower,
+    /// tUPPER
+    Upper,
+    /// tCRIT
+    Critical,
+}
+
+impl Limit {
+    /// all limits, in the order the drivers write and verify them
+    pub(crate) const ALL: [Limit; 3] = [Limit::Lower, Limit::Upper, Limit::Critical];
+
+    /// empty register of this limit
+    pub(crate) fn register(self) -> Register {
+        match self {
+            Limit::Lower => *reg_temp_alert_lower::new().register(),
+            Limit::Upper => *reg_temp_alert_upper::new().register(),
+            Limit::Critical => *reg_temp_alert_crit::new().register(),
+        }
+    }
+
+    /// register of this limit holding `temp`, rounded down to 0.25 °C
+    pub(crate) fn encode(self, temp: Celsius) -> Result<Register, ValueError> {
+        let mut reg = self.register();
+        reg.set_celsius(temp)?;
+        Ok(reg)
+    }
+}
+
+/// Validated set of alert limits.
+///
+/// Limits are ordered `lower < upper <= critical`, multiples of 0.25 °C and within ±256 °C.
+///
+/// # Example
+///
+/// ```
+/// use mcp9808::alert::AlertWindow;
+/// use mcp9808::celsius::Celsius;
+/// use mcp9808::reg_conf::Hysteresis;
+///
+/// let window = AlertWindow::new(
+///     Celsius::from_degrees(-20),
+///     Celsius::from_degrees(40),
+///     Celsius::from_degrees(60),
+///     Hysteresis::Deg_1_5C,
+/// );
+/// assert!(window.is_ok());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AlertWindow {
+    lower: Celsius,
+    upper: Celsius,
+    critical: Celsius,
+    hysteresis: Hysteresis,
+}
+
+impl AlertWindow {
+    pub fn new(
+        lower: Celsius,
+        upper: Celsius,
+        critical: Celsius,
+        hysteresis: Hysteresis,
+    ) -> Result<Self, ValueError> {
+        for (limit, val) in [
+            (Limit::Lower, lower),
+            (Limit::Upper, upper),
+            (Limit::Critical, critical),
+        ] {
+            if val.as_sixteenths() & MASK_LIMIT_FRACT != 0 {
+                return Err(ValueError::LimitNotQuantized(limit));
+            }
+            encode_limit(val)?;
+        }
+
+        if lower >= upper || critical < upper {
+            return Err(ValueError::InvalidAlertWindow);
+        }
+
+        Ok(AlertWindow {
+            lower,
+            upper,
+            critical,
+            hysteresis,
+        })
+    }
+
+    pub fn lower(&self) -> Celsius {
+        self.lower
+    }
+
+    pub fn upper(&self) -> Celsius {
+        self.upper
+    }
+
+    pub fn critical(&self) -> Celsius {
+        self.critical
+    }
+
+    pub fn hysteresis(&self) -> Hysteresis {
+        self.hysteresis
+    }
+
+    /// value of `limit`
+    pub fn limit(&self, limit: Limit) -> Celsius {
+        match limit {
+            Limit::Lower => self.lower,
+            Limit::Upper => self.upper,
+            Limit::Critical => self.critical,
+        }
+    }
+
+    /// sets the hysteresis in `conf` and enables the alert output
+    pub(crate) fn configure(&self, conf: &mut ConfigurationRegister) {
+        conf.set_hysteresis(self.hysteresis);
+        conf.set_alert_control(AlertControl::Enabled);
+    }
+}
+
+/// Alert limits around a temperature, as programmed by `MCP9808::enable_wake_on_change()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChangeWindow {
+    /// temperature the window is centred on
+    pub temperature: Celsius,
+    /// tLOWER, `temperature - delta` rounded down to 0.25 °C
+    pub lower: Celsius,
+    /// tUPPER, `temperature + delta` rounded up to 0.25 °C
+    pub upper: Celsius,
+}
+
+impl ChangeWindow {
+    /// Window that trips once the temperature moved by more than `delta`, which must be positive.
+    pub fn around(temperature: Celsius, delta: Celsius) -> Result<Self, ValueError> {
+        if delta <= Celsius::ZERO {
+            return Err(ValueError::InvalidAlertWindow);
+        }
+        // in i32, so neither the sum nor the rounding saturates or overflows
+        let fract = i32::from(MASK_LIMIT_FRACT);
+        let temp = i32::from(temperature.as_sixteenths());
+        let delta = i32::from(delta.as_sixteenths());
+        let lower = (temp - delta) & !fract;
+        let upper = (temp + delta + fract) & !fract;
+        let to_limit = |raw: i32| {
+            i16::try_from(raw)
+                .map(Celsius::from_sixteenths)
+                .map_err(|_| ValueError::OutOfRange)
+        };
+        let window = ChangeWindow {
+            temperature,
+            lower: to_limit(lower)?,
+            upper: to_limit(upper)?,
+        };
+        encode_limit(window.lower)?;
+        encode_limit(window.upper)?;
+        Ok(window)
+    }
+
+    /// interrupt-mode alerts on all limits in `conf`
+    pub(crate) fn configure(conf: &mut ConfigurationRegister) {
+        conf.set_alert_mode(AlertMode::Interrupt);
+        conf.set_alert_select(AlertSelect::All);
+        conf.set_alert_control(AlertControl::Enabled);
+    }
+}
+
+/// Alert flags and ambient temperature, as read by `MCP9808::service_alert()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AlertEvent {
+    /// TA ≥ tCRIT
+    pub critical: bool,
+    /// TA > tUPPER
+    pub upper: bool,
+    /// TA < tLOWER
+    pub lower: bool,
+    /// ambient temperature read together with the flags
+    pub temperature: Celsius,
+    /// whether the interrupt was cleared
+    pub cleared: bool,
+}
+
+impl AlertEvent {
+    /// flags and temperature of `reg`, not cleared yet
+    pub fn from_register(reg: &TemperatureRegister, res: ResolutionVal) -> Self {
+        AlertEvent {
+            critical: reg.is_alert_critical(),
+            upper: reg.is_alert_upper(),
+            lower: reg.is_alert_lower(),
+            temperature: reg.get_celsius(res),
+            cleared: false,
+        }
+    }
+
+    /// any limit tripped
+    pub fn is_tripped(&self) -> bool {
+        self.critical || self.upper || self.lower
+    }
+}
+
+/// Whether setting the Interrupt Clear bit in `conf` would deassert the alert output.
+///
+/// Only an asserted interrupt can be cleared, not in shutdown, and not while TA ≥ tCRIT as the
+/// output then acts as a comparator.
+pub(crate) fn is_clearable(conf: &ConfigurationRegister, event: &AlertEvent) -> bool {
+    conf.get_alert_mode() == AlertMode::Interrupt
+        && conf.get_alert_status() == AlertStatus::Asserted
+        && conf.get_shutdown_mode() == ShutdownMode::Continuous
+        && !event.critical
+}
+
+/// `conf` with the Interrupt Clear bit set, `None` if it would not clear the alert of `event`
+pub(crate) fn interrupt_clear(
+    mut conf: ConfigurationRegister,
+    event: &AlertEvent,
+) -> Option<ConfigurationRegister> {
+    if !is_clearable(&conf, event) {
+        return None;
+    }
+    conf.set_interrupt_clear(InterruptClear::ClearInterruptOutput);
+    Some(conf)
+}
+
+/// Expected alert flags and ALERT output after one temperature step of `AlertPredictor`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AlertPrediction {
+    /// TA ≥ tCRIT flag
+    pub critical: bool,
+    /// TA > tUPPER flag
+    pub upper: bool,
+    /// TA < tLOWER flag
+    pub lower: bool,
+    /// ALERT output asserted
+    pub asserted: bool,
+    /// level of the ALERT pin, given the polarity
+    pub pin_high: bool,
+}
+
+/// Hardware-free model of the alert logic, following the timing diagram of the datasheet.
+///
+/// tUPPER and tCRIT release at `limit - hysteresis` when cooling down, tLOWER trips at
+/// `tLOWER - hysteresis` and releases at tLOWER. In comparator mode the output follows the flags,
+/// in interrupt mode each change of the tUPPER/tLOWER flags asserts it until
+/// `clear_interrupt()`, while TA ≥ tCRIT it is asserted regardless. In shutdown nothing changes.
+///
+/// # Example
+///
+/// ```
+/// use mcp9808::alert::AlertPredictor;
+/// use mcp9808::celsius::Celsius;
+/// use mcp9808::reg_temp_generic::WritableTempRegister;
+/// use mcp9808::{reg_conf, reg_temp_alert_crit, reg_temp_alert_lower, reg_temp_alert_upper};
+///
+/// let mut lower = reg_temp_alert_lower::new();
+/// lower.set_celsius(Celsius::from_degrees(10)).unwrap();
+/// let mut upper = reg_temp_alert_upper::new();
+/// upper.set_celsius(Celsius::from_degrees(30)).unwrap();
+/// let mut crit = reg_temp_alert_crit::new();
+/// crit.set_celsius(Celsius::from_degrees(50)).unwrap();
+///
+/// let predictor = AlertPredictor::new(&reg_conf::new(), &lower, &upper, &crit);
+/// let temps = [20, 31, 25].map(Celsius::from_degrees);
+/// let flags: Vec<bool> = predictor.predict(temps).map(|p| p.upper).collect();
+/// assert_eq!(flags, [false, true, false]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AlertPredictor {
+    mode: AlertMode,
+    polarity: AlertPolarity,
+    select: AlertSelect,
+    control: AlertControl,
+    shutdown: ShutdownMode,
+    hysteresis: Celsius,
+    lower: Celsius,
+    upper: Celsius,
+    critical: Celsius,
+    state: AlertPrediction,
+    interrupt: bool,
+}
+
+impl AlertPredictor {
+    /// Starts with no flag set and the output deasserted.
+    pub fn new(
+        conf: &ConfigurationRegister,
+        lower: &LowerLimitRegister,
+        upper: &UpperLimitRegister,
+        critical: &CriticalLimitRegister,
+    ) -> Self {
+        let polarity = conf.get_alert_polarity();
+        AlertPredictor {
+            mode: conf.get_alert_mode(),
+            polarity,
+            select: conf.get_alert_select(),
+            control: conf.get_alert_control(),
+            shutdown: conf.get_shutdown_mode(),
+            hysteresis: conf.get_hysteresis().to_celsius(),
+            lower: lower.get_celsius(ResolutionVal::Deg_0_25C),
+            upper: upper.get_celsius(ResolutionVal::Deg_0_25C),
+            critical: critical.get_celsius(ResolutionVal::Deg_0_25C),
+            state: AlertPrediction {
+                critical: false,
+                upper: false,
+                lower: false,
+                asserted: false,
+                pin_high: polarity.pin_level(false),
+            },
+            interrupt: false,
+        }
+    }
+
+    /// Takes over changed configuration and limits, keeping the flags and a pending interrupt.
+    pub fn reconfigure(
+        &mut self,
+        conf: &ConfigurationRegister,
+        lower: &LowerLimitRegister,
+        upper: &UpperLimitRegister,
+        critical: &CriticalLimitRegister,
+    ) {
+        let (state, interrupt) = (self.state, self.interrupt);
+        *self = Self::new(conf, lower, upper, critical);
+        self.state = AlertPrediction {
+            pin_high: self.polarity.pin_level(state.asserted),
+            ..state
+        };
+        self.interrupt = interrupt;
+    }
+
+    /// prediction of the last step
+    pub fn state(&self) -> AlertPrediction {
+        self.state
+    }
+
+    /// Feeds the next temperature reading.
+    pub fn step(&mut self, temp: Celsius) -> AlertPrediction {
+        if self.shutdown == ShutdownMode::Shutdown {
+            return self.state;
+        }
+
+        let prev = self.state;
+        let hyst = self.hysteresis;
+        let critical = if prev.critical {
+            temp >= self.critical - hyst
+        } else {
+            temp >= self.critical
+        };
+        let upper = if prev.upper {
+            temp > self.upper - hyst
+        } else {
+            temp > self.upper
+        };
+        let lower = if prev.lower {
+            temp < self.lower
+        } else {
+            temp < self.lower - hyst
+        };
+
+        let window = self.select == AlertSelect::All;
+        let window_changed = upper != prev.upper || lower != prev.lower;
+        if self.control == AlertControl::Enabled && window && window_changed {
+            self.interrupt = true;
+        }
+        let window_asserted = match self.mode {
+            AlertMode::Comparator => window && (upper || lower),
+            AlertMode::Interrupt => self.interrupt,
+        };
+        let asserted = self.control == AlertControl::Enabled && (critical || window_asserted);
+
+        self.state = AlertPrediction {
+            critical,
+            upper,
+            lower,
+            asserted,
+            pin_high: self.polarity.pin_level(asserted),
+        };
+        self.state
+    }
+
+    /// Models the host setting the Interrupt Clear bit, no effect while TA ≥ tCRIT.
+    pub fn clear_interrupt(&mut self) {
+        if self.shutdown == ShutdownMode::Continuous && !self.state.critical {
+            self.interrupt = false;
+            self.state.asserted = false;
+            self.state.pin_high = self.polarity.pin_level(false);
+        }
+    }
+
+    /// Predictions for each temperature in turn, the interrupt is never cleared.
+    pub fn predict<I>(mut self, temps: I) -> impl Iterator<Item = AlertPrediction>
+    where
+        I: IntoIterator<Item = Celsius>,
+    {
+        temps.into_iter().map(move |temp| self.step(temp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Read;
+
+    fn window(lower: i32, upper: i32, critical: i32) -> Result<AlertWindow, ValueError> {
+        AlertWindow::new(
+            Celsius::from_millicelsius(lower),
+            Celsius::from_millicelsius(upper),
+            Celsius::from_millicelsius(critical),
+            Hysteresis::Deg_0_0C,
+        )
+    }
+
+    #[test]
+    fn validation() {
+        assert!(window(-10_000, 30_250, 30_250).is_ok());
+        assert!(window(-10_250, -5_000, 85_000).is_ok());
+
+        assert!(matches!(
+            window(30_000, 20_000, 85_000),
+            Err(ValueError::InvalidAlertWindow)
+        ));
+        assert!(matches!(
+            window(20_000, 20_000, 85_000),
+            Err(ValueError::InvalidAlertWindow)
+        ));
+        assert!(matches!(
+            window(20_000, 40_000, 30_000),
+            Err(ValueError::InvalidAlertWindow)
+        ));
+        assert!(matches!(
+            window(20_000, 40_125, 85_000),
+            Err(ValueError::LimitNotQuantized(Limit::Upper))
+        ));
+        assert!(matches!(
+            window(-125, 40_000, 85_000),
+            Err(ValueError::LimitNotQuantized(Limit::Lower))
+        ));
+        assert!(matches!(
+            window(20_000, 40_000, 300_000),
+            Err(ValueError::OutOfRange)
+        ));
+    }
+
+    #[test]
+    fn alert_event() {
+        let mut temp = crate::reg_temp::new();
+        // 31 °C, TA > tUPPER
+        temp.register_mut().set_buf([0x41, 0xf0]);
+        let event = AlertEvent::from_register(&temp, ResolutionVal::Deg_0_0625C);
+        assert!(event.upper && !event.lower && !event.critical);
+        assert_eq!(event.temperature, Celsius::from_degrees(31));
+
+        let mut conf = crate::reg_conf::new();
+        conf.set_alert_mode(AlertMode::Interrupt);
+        assert!(!is_clearable(&conf, &event));
+        conf.set_alert_status(AlertStatus::Asserted);
+        assert!(is_clearable(&conf, &event));
+
+        // tCRIT is always comparator style
+        let critical = AlertEvent {
+            critical: true,
+            ..event
+        };
+        assert!(!is_clearable(&conf, &critical));
+
+        conf.set_shutdown_mode(ShutdownMode::Shutdown);
+        assert!(!is_clearable(&conf, &event));
+
+        conf.set_shutdown_mode(ShutdownMode::Continuous);
+        conf.set_alert_mode(AlertMode::Comparator);
+        assert!(!is_clearable(&conf, &event));
+    }
+
+    #[test]
+    fn change_window() {
+        let around = |mc, delta| {
+            ChangeWindow::around(
+                Celsius::from_millicelsius(mc),
+                Celsius::from_millicelsius(delta),
+            )
+        };
+
+        let window = around(21_500, 1_000).unwrap();
+        assert_eq!(window.lower, Celsius::from_millicelsius(20_500));
+        assert_eq!(window.upper, Celsius::from_millicelsius(22_500));
+
+        // -3.0625 °C, widened to 0.25 °C steps
+        let window = ChangeWindow::around(
+            Celsius::from_sixteenths(-49),
+            Celsius::from_millicelsius(500),
+        )
+        .unwrap();
+        assert_eq!(window.lower, Celsius::from_millicelsius(-3_750));
+        assert_eq!(window.upper, Celsius::from_millicelsius(-2_500));
+
+        assert!(matches!(
+            around(20_000, 0),
+            Err(ValueError::InvalidAlertWindow)
+        ));
+        assert!(matches!(
+            around(250_000, 10_000),
+            Err(ValueError::OutOfRange)
+        ));
+
+        // far outside the limit range, without overflowing on the way
+        for (temp, delta) in [
+            (Celsius::ZERO, Celsius::MAX),
+            (Celsius::MAX, Celsius::MAX),
+            (Celsius::MIN, Celsius::MAX),
+            (Celsius::MAX, Celsius::from_sixteenths(1)),
+        ] {
+            let window = ChangeWindow::around(temp, delta);
+            assert!(matches!(window, Err(ValueError::OutOfRange)));
+        }
+        assert!(matches!(
+            ChangeWindow::around(Celsius::ZERO, Celsius::MIN),
+            Err(ValueError::InvalidAlertWindow)
+        ));
+    }
+
+    fn predictor(conf: ConfigurationRegister) -> AlertPredictor {
+        let mut lower = crate::reg_temp_alert_lower::new();
+        lower
+            .register_mut()
+            .set_u16(encode_limit(Celsius::from_degrees(10)).unwrap());
+        let mut upper = crate::reg_temp_alert_upper::new();
+        upper
+            .register_mut()
+            .set_u16(encode_limit(Celsius::from_degrees(30)).unwrap());
+        let mut crit = crate::reg_temp_alert_crit::new();
+        crit.register_mut()
+            .set_u16(encode_limit(Celsius::from_degrees(50)).unwrap());
+        AlertPredictor::new(&conf, &lower, &upper, &crit)
+    }
+
+    fn asserted(predictor: AlertPredictor, temps: &[i16]) -> Vec<bool> {
+        predictor
+            .predict(temps.iter().map(|t| Celsius::from_degrees(*t)))
+            .map(|p| p.asserted)
+            .collect()
+    }
+
+    #[test]
+    fn predict_comparator() {
+        let mut conf = crate::reg_conf::new();
+        conf.set_alert_control(AlertControl::Enabled);
+        conf.set_hysteresis(Hysteresis::Deg_3_0C);
+        // tLOWER trips at tLOWER - hysteresis, the others release at limit - hysteresis
+        let temps = [20, 31, 28, 27, 20, 8, 6, 9, 10, 52, 48, 46];
+        assert_eq!(
+            asserted(predictor(conf), &temps),
+            [false, true, true, false, false, false, true, true, false, true, true, true]
+        );
+
+        conf.set_alert_select(AlertSelect::TCritOnly);
+        assert_eq!(
+            asserted(predictor(conf), &temps),
+            [false, false, false, false, false, false, false, false, false, true, true, false]
+        );
+
+        conf.set_alert_control(AlertControl::Disabled);
+        assert!(!asserted(predictor(conf), &temps).contains(&true));
+    }
+
+    #[test]
+    fn predict_interrupt() {
+        let mut conf = crate::reg_conf::new();
+        conf.set_alert_control(AlertControl::Enabled);
+        conf.set_alert_mode(AlertMode::Interrupt);
+        let mut p = predictor(conf);
+
+        let temp = Celsius::from_degrees;
+        let step = p.step(temp(31));
+        assert!(step.upper && step.asserted && !step.pin_high);
+        // stays asserted until cleared
+        assert!(p.step(temp(32)).asserted);
+        p.clear_interrupt();
+        assert!(!p.step(temp(32)).asserted);
+        // falling below tUPPER is an event as well
+        assert!(p.step(temp(29)).asserted);
+        p.clear_interrupt();
+
+        // tCRIT can not be cleared
+        assert!(p.step(temp(51)).critical);
+        p.clear_interrupt();
+        assert!(p.step(temp(51)).asserted);
+        let step = p.step(temp(45));
+        assert!(!step.critical && step.asserted);
+        p.clear_interrupt();
+        assert!(!p.step(temp(45)).asserted);
+    }
+}
@@ -0,0 +1,71 @@
+//! ALERT output paired with an input pin
+
+use crate::alert::AlertEvent;
+use crate::error::Error;
+use crate::interface::RegisterInterface;
+use crate::reg_conf::{AlertPolarity, Configuration};
+use crate::MCP9808;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{Error as _, InputPin};
+
+/// MCP9808 together with the input pin wired to its ALERT output, see `MCP9808::with_alert_pin()`.
+pub struct WithAlertPin<I2C, P> {
+    dev: MCP9808<I2C>,
+    pin: P,
+    /// polarity last read from the device
+    polarity: AlertPolarity,
+}
+
+impl<I2C, P> WithAlertPin<I2C, P>
+where
+    I2C: RegisterInterface,
+    P: InputPin,
+{
+    pub(crate) fn new(dev: MCP9808<I2C>, pin: P, polarity: AlertPolarity) -> Self {
+        WithAlertPin { dev, pin, polarity }
+    }
+
+    /// The driver, call `sync_polarity()` after changing the alert polarity through it.
+    pub fn device(&mut self) -> &mut MCP9808<I2C> {
+        &mut self.dev
+    }
+
+    /// Polarity the pin level is interpreted with.
+    pub fn polarity(&self) -> AlertPolarity {
+        self.polarity
+    }
+
+    /// Reads the alert polarity from the device.
+    pub fn sync_polarity(&mut self) -> Result<AlertPolarity, Error<I2C::Error>> {
+        self.polarity = self.dev.read_configuration()?.get_alert_polarity();
+        Ok(self.polarity)
+    }
+
+    /// Whether the ALERT output is asserted, without any bus access.
+    pub fn is_alert_asserted(&mut self) -> Result<bool, Error<I2C::Error>> {
+        let high = self.pin.is_high().map_err(|e| Error::Pin(e.kind()))?;
+        Ok(self.polarity.is_asserted(high))
+    }
+
+    /// Polls the pin every `interval_ms` until the ALERT output is asserted, then services the
+    /// alert.
+    ///
+    /// See `MCP9808::service_alert()`, the interrupt is cleared if possible. In comparator mode the
+    /// output stays asserted until the temperature is back inside the limits, so this returns
+    /// right away while it is.
+    pub fn wait_for_alert<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+        interval_ms: u32,
+    ) -> Result<AlertEvent, Error<I2C::Error>> {
+        while !self.is_alert_asserted()? {
+            delay.delay_ms(interval_ms);
+        }
+        self.dev.service_alert()
+    }
+
+    /// release resources
+    pub fn free(self) -> (MCP9808<I2C>, P) {
+        (self.dev, self.pin)
+    }
+}
@@ -0,0 +1,265 @@
+//! Several sensors sharing one bus
+
+use crate::address::SlaveAddress;
+use crate::celsius::Celsius;
+use crate::device::DeviceState;
+use crate::error::Error;
+use crate::interface::RegisterInterface;
+use crate::reg_conf::ConfigBuilder;
+use crate::reg_res::ResolutionVal;
+use crate::MCP9808;
+
+/// Per-sensor state of a `SensorArray`
+pub struct Sensor {
+    label: &'static str,
+    /// driver state without the bus
+    state: DeviceState,
+    /// configuration last applied with `SensorArray::apply_config_all()`
+    config: Option<ConfigBuilder>,
+}
+
+impl Sensor {
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    pub fn address(&self) -> u8 {
+        self.state.addr
+    }
+
+    /// resolution last read from or written to the device
+    pub fn resolution(&self) -> ResolutionVal {
+        self.state.resolution
+    }
+
+    /// configuration last applied successfully, `None` until then
+    pub fn config(&self) -> Option<ConfigBuilder> {
+        self.config
+    }
+}
+
+/// Up to eight sensors on one bus, which is owned once for all of them.
+///
+/// Bulk operations visit every sensor and report the result per sensor, so one failing sensor
+/// does not abort the sweep. A single driver owns the bus, the state it caches is swapped in for
+/// the sensor it talks to.
+///
+/// # Example
+///
+/// ```
+/// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+/// use mcp9808::address::SlaveAddress;
+/// use mcp9808::array::SensorArray;
+///
+/// # let i2c = Mock::new(&[
+/// #     Transaction::write_read(0x18, vec![5], vec![0x01, 0x94]),
+/// #     Transaction::write_read(0x19, vec![5], vec![0x01, 0x50]),
+/// # ]);
+/// let inlet = SlaveAddress::Default;
+/// let outlet = SlaveAddress::Alternative { a2: false, a1: false, a0: true };
+/// let mut array = SensorArray::new(i2c, [(inlet, "inlet"), (outlet, "outlet")]).unwrap();
+///
+/// let temps = array.read_all_celsius();
+/// for (sensor, temp) in array.sensors().iter().zip(temps) {
+///     // sensor.label(), temp
+/// }
+/// # array.free().done();
+/// ```
+pub struct SensorArray<I2C, const N: usize> {
+    dev: MCP9808<I2C>,
+    sensors: [Sensor; N],
+}
+
+impl<I2C, const N: usize> SensorArray<I2C, N>
+where
+    I2C: RegisterInterface,
+{
+    /// Fails if there are more than eight sensors or two of them share an address.
+    pub fn new(
+        i2c: I2C,
+        sensors: [(SlaveAddress, &'static str); N],
+    ) -> Result<Self, Error<I2C::Error>> {
+        if N > 8 {
+            return Err(Error::TooManySensors(N));
+        }
+        for (i, (addr, _)) in sensors.iter().enumerate() {
+            if sensors.iter().skip(i + 1).any(|(other, _)| other == addr) {
+                return Err(Error::DuplicateAddress((*addr).into()));
+            }
+        }
+
+        Ok(SensorArray {
+            dev: MCP9808::new(i2c),
+            sensors: sensors.map(|(addr, label)| Sensor {
+                label,
+                state: DeviceState::new(addr),
+                config: None,
+            }),
+        })
+    }
+
+    pub fn sensors(&self) -> &[Sensor; N] {
+        &self.sensors
+    }
+
+    /// Runs `f` with the driver of sensor `index`, `None` if there is no such sensor.
+    ///
+    /// State the driver caches, such as the resolution, is kept for the next access.
+    pub fn with_sensor<T, F>(&mut self, index: usize, f: F) -> Option<Result<T, Error<I2C::Error>>>
+    where
+        F: FnOnce(&mut MCP9808<I2C>) -> Result<T, Error<I2C::Error>>,
+    {
+        let sensor = self.sensors.get_mut(index)?;
+        self.dev.state = sensor.state;
+        let res = f(&mut self.dev);
+        sensor.state = self.dev.state;
+        Some(res)
+    }
+
+    /// `f` for every sensor in turn
+    fn sweep<T, F>(&mut self, mut f: F) -> [Result<T, Error<I2C::Error>>; N]
+    where
+        F: FnMut(&mut MCP9808<I2C>, &mut Sensor) -> Result<T, Error<I2C::Error>>,
+    {
+        let dev = &mut self.dev;
+        self.sensors.each_mut().map(|sensor| {
+            dev.state = sensor.state;
+            let res = f(dev, sensor);
+            sensor.state = dev.state;
+            res
+        })
+    }
+
+    /// Reads all temperatures, each decoded with the resolution cached for its sensor.
+    pub fn read_all_celsius(&mut self) -> [Result<Celsius, Error<I2C::Error>>; N] {
+        self.sweep(|dev, _| dev.read_celsius())
+    }
+
+    /// Reads the resolution of all sensors and caches it.
+    pub fn sync_resolution_all(&mut self) -> [Result<ResolutionVal, Error<I2C::Error>>; N] {
+        self.sweep(|dev, _| dev.sync_resolution())
+    }
+
+    /// Applies `config` to all sensors, see `MCP9808::apply_config()`.
+    pub fn apply_config_all(
+        &mut self,
+        config: &ConfigBuilder,
+    ) -> [Result<(), Error<I2C::Error>>; N] {
+        self.sweep(|dev, sensor| {
+            dev.apply_config(config)?;
+            sensor.config = Some(*config);
+            Ok(())
+        })
+    }
+
+    /// release resources
+    pub fn free(self) -> I2C {
+        self.dev.free()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reg_conf::{AlertControl, ConfigDiff};
+    use embedded_hal::i2c::ErrorKind;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    const SENSORS: [(SlaveAddress, &str); 3] = [
+        (SlaveAddress::Default, "inlet"),
+        (
+            SlaveAddress::Alternative {
+                a2: false,
+                a1: false,
+                a0: true,
+            },
+            "cpu",
+        ),
+        (
+            SlaveAddress::Alternative {
+                a2: true,
+                a1: true,
+                a0: true,
+            },
+            "outlet",
+        ),
+    ];
+
+    #[test]
+    fn read_all() {
+        let expectations = [
+            Transaction::write_read(0x19, vec![0b1000], vec![0b01]),
+            Transaction::write_read(0x18, vec![0b0101], vec![0x01, 0x97]),
+            Transaction::write_read(0x19, vec![0b0101], vec![0x01, 0x97]),
+            Transaction::write_read(0x1f, vec![0b0101], vec![0x01, 0x97])
+                .with_error(ErrorKind::Other),
+        ];
+        let mut array = SensorArray::new(Mock::new(&expectations), SENSORS).unwrap();
+
+        let res = array.with_sensor(1, |dev| dev.sync_resolution());
+        assert!(matches!(res, Some(Ok(ResolutionVal::Deg_0_25C))));
+        assert!(array.with_sensor(3, |dev| dev.read_celsius()).is_none());
+        assert_eq!(array.sensors()[1].resolution(), ResolutionVal::Deg_0_25C);
+        assert_eq!(array.sensors()[2].label(), "outlet");
+
+        let [inlet, cpu, outlet] = array.read_all_celsius();
+        assert_eq!(inlet.unwrap(), Celsius::from_sixteenths(0x197));
+        assert_eq!(cpu.unwrap(), Celsius::from_millicelsius(25_250));
+        assert!(matches!(outlet, Err(Error::I2c(ErrorKind::Other))));
+
+        array.free().done();
+    }
+
+    #[test]
+    fn new() {
+        let mut nine = [(SlaveAddress::Default, "board"); 9];
+        for (i, (addr, _)) in nine.iter_mut().enumerate() {
+            *addr = SlaveAddress::Alternative {
+                a2: i & 0b100 != 0,
+                a1: i & 0b010 != 0,
+                a0: i & 0b001 != 0,
+            };
+        }
+        // rejected arrays drop their bus, clones share the expectations
+        let mut i2c = Mock::new(&[]);
+        let res = SensorArray::new(i2c.clone(), nine);
+        assert!(matches!(res, Err(Error::TooManySensors(9))));
+
+        let [default, cpu, _] = SENSORS;
+        let res = SensorArray::new(i2c.clone(), [default, cpu, (cpu.0, "gpu")]);
+        assert!(matches!(res, Err(Error::DuplicateAddress(0x19))));
+
+        let array = SensorArray::new(i2c.clone(), SENSORS).unwrap();
+        assert_eq!(array.sensors()[2].address(), 0x1f);
+        i2c.done();
+    }
+
+    #[test]
+    fn apply_config_all() {
+        let expectations = [
+            Transaction::write_read(0x18, vec![0b0001], vec![0x00, 0x00]),
+            Transaction::write(0x18, vec![0b0001, 0x00, 0x08]),
+            Transaction::write_read(0x18, vec![0b0001], vec![0x00, 0x08]),
+            Transaction::write_read(0x19, vec![0b0001], vec![0x00, 0x00]),
+            Transaction::write(0x19, vec![0b0001, 0x00, 0x08]),
+            Transaction::write_read(0x19, vec![0b0001], vec![0x00, 0x00]),
+            Transaction::write_read(0x1f, vec![0b0001], vec![0x00, 0x00]),
+            Transaction::write(0x1f, vec![0b0001, 0x00, 0x08]),
+            Transaction::write_read(0x1f, vec![0b0001], vec![0x00, 0x08]),
+        ];
+        let mut array = SensorArray::new(Mock::new(&expectations), SENSORS).unwrap();
+        let config = ConfigBuilder::new().alert_control(AlertControl::Enabled);
+
+        let [inlet, cpu, outlet] = array.apply_config_all(&config);
+        assert!(inlet.is_ok() && outlet.is_ok());
+        let diff = ConfigDiff {
+            alert_control: true,
+            ..ConfigDiff::default()
+        };
+        assert!(matches!(cpu, Err(Error::ConfigNotApplied(d)) if d == diff));
+        assert_eq!(array.sensors()[0].config(), Some(config));
+        assert_eq!(array.sensors()[1].config(), None);
+
+        array.free().done();
+    }
+}
@@ -0,0 +1,726 @@
+//! Async driver, built on the `embedded-hal-async` I2C traits.
+//!
+//! Register encoding and decoding, the cached device state and the checks between bus accesses
+//! are shared with the blocking driver, only the bus access differs.
+
+use crate::address::{self, Found, SlaveAddress};
+use crate::alert::{self, AlertEvent, AlertWindow, ChangeWindow, Limit};
+use crate::celsius::Celsius;
+use crate::device::{self, DeviceState};
+use crate::error::Error;
+use crate::interface;
+use crate::prelude;
+use crate::reg_conf::{
+    self, AlertPolarity, ConfigBuilder, Configuration, ConfigurationRegister, CriticalLock,
+    Hysteresis, InterruptClear, LockState, ShutdownMode, WindowLock,
+};
+use crate::reg_device_id::{self, DeviceIdRegister};
+use crate::reg_manuf_id::{self, ManufacturerIdRegister};
+use crate::reg_res::{self, Resolution, ResolutionRegister, ResolutionVal};
+use crate::reg_temp::{self, TemperatureRegister};
+use crate::reg_temp_alert_crit::{self, CriticalLimitRegister};
+use crate::reg_temp_alert_lower::{self, LowerLimitRegister};
+use crate::reg_temp_alert_upper::{self, UpperLimitRegister};
+use crate::reg_temp_generic::ReadableTempRegister;
+use crate::snapshot::{RawRegisters, RegisterSnapshot};
+use core::fmt::Debug;
+use embedded_hal::digital::{Error as _, ErrorType, InputPin};
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, Operation, SevenBitAddress};
+
+/// async access to the registers of the device at a bus address, see
+/// `interface::RegisterInterface`
+#[allow(async_fn_in_trait)]
+pub trait RegisterInterface {
+    type Error: Debug;
+
+    /// reads `buf.len()` bytes from the register at `ptr`
+    async fn read_register(&mut self, addr: u8, ptr: u8, buf: &mut [u8])
+        -> Result<(), Self::Error>;
+
+    /// reads `buf.len()` bytes from the register the pointer was last set to
+    async fn read_current_register(&mut self, addr: u8, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// writes `data` to the register at `ptr`
+    async fn write_register(&mut self, addr: u8, ptr: u8, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// reads several `(pointer, buffer)` registers, one after another by default
+    async fn read_registers(
+        &mut self,
+        addr: u8,
+        regs: &mut [(u8, &mut [u8])],
+    ) -> Result<(), Self::Error> {
+        for (ptr, buf) in regs.iter_mut() {
+            self.read_register(addr, *ptr, buf).await?;
+        }
+        Ok(())
+    }
+}
+
+/// default transport: sets the pointer, then reads or writes in the same transaction
+impl<I2C> RegisterInterface for I2C
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    type Error = I2C::Error;
+
+    async fn read_register(
+        &mut self,
+        addr: u8,
+        ptr: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.write_read(addr, &[ptr], buf).await
+    }
+
+    async fn read_current_register(&mut self, addr: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.read(addr, buf).await
+    }
+
+    async fn write_register(&mut self, addr: u8, ptr: u8, data: &[u8]) -> Result<(), Self::Error> {
+        match interface::write_buf(&mut [0; 3], ptr, data) {
+            Some(buf) => self.write(addr, buf).await,
+            // registers are at most 2 bytes, anything longer follows the pointer without a restart
+            None => {
+                self.transaction(
+                    addr,
+                    &mut [Operation::Write(&[ptr]), Operation::Write(data)],
+                )
+                .await
+            }
+        }
+    }
+
+    /// up to 8 registers per transaction, with a repeated start instead of a stop in between
+    async fn read_registers(
+        &mut self,
+        addr: u8,
+        regs: &mut [(u8, &mut [u8])],
+    ) -> Result<(), Self::Error> {
+        for chunk in regs.chunks_mut(interface::BATCH) {
+            let mut ops = core::array::from_fn(|_| Operation::Write(&[]));
+            self.transaction(addr, interface::batch_ops(&mut ops, chunk))
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// MCP9808 async Driver
+pub struct MCP9808<I2C> {
+    i2c: I2C,
+    state: DeviceState,
+}
+
+impl<I2C> MCP9808<I2C>
+where
+    I2C: RegisterInterface,
+{
+    /// Creates a new driver from an I2C peripheral or another `RegisterInterface`.
+    pub fn new(i2c: I2C) -> Self {
+        MCP9808 {
+            i2c,
+            state: DeviceState::new(SlaveAddress::Default),
+        }
+    }
+
+    /// Creates a new driver and verifies that an MCP9808 answers at `addr`.
+    pub async fn probe(i2c: I2C, addr: SlaveAddress) -> Result<Self, Error<I2C::Error>> {
+        let mut dev = Self::new(i2c);
+        dev.set_address(addr);
+        dev.verify_identity().await?;
+        Ok(dev)
+    }
+
+    /// Checks manufacturer and device ID and records the device revision.
+    pub async fn verify_identity(&mut self) -> Result<(), Error<I2C::Error>> {
+        let manuf = self.read_manufacturer_id().await?;
+        device::check_manufacturer(&manuf)?;
+        let device = self.read_device_id().await?;
+        self.state.identify(&device)
+    }
+
+    /// Device revision, `None` until the identity was verified.
+    pub fn device_revision(&self) -> Option<u8> {
+        self.state.revision
+    }
+
+    /// Change i2c address
+    pub fn set_address(&mut self, addr: SlaveAddress) -> u8 {
+        self.state.set_address(addr)
+    }
+
+    /// Reads the register the device already points to without writing the pointer again.
+    ///
+    /// Only enable this if nothing else accesses the device, such as another driver instance or
+    /// bus master, the driver does not notice the pointer changing under it.
+    pub fn set_pointer_cache(&mut self, enabled: bool) {
+        self.state.set_pointer_cache(enabled);
+    }
+
+    /// release resources
+    pub fn free(self) -> I2C {
+        self.i2c
+    }
+
+    /// Pairs the driver with the input pin wired to the ALERT output.
+    ///
+    /// Reads the configured alert polarity from the device.
+    pub async fn with_alert_pin<P: ErrorType>(
+        mut self,
+        pin: P,
+    ) -> Result<WithAlertPin<I2C, P>, Error<I2C::Error>> {
+        let polarity = self.read_configuration().await?.get_alert_polarity();
+        Ok(WithAlertPin {
+            dev: self,
+            pin,
+            polarity,
+        })
+    }
+
+    async fn read_register<T: prelude::Read>(
+        &mut self,
+        mut reg: T,
+    ) -> Result<T, Error<I2C::Error>> {
+        let raw = reg.register_mut();
+        let ptr = raw.get_ptr();
+        let res = if self.state.is_pointing_to(ptr) {
+            self.i2c
+                .read_current_register(self.state.addr, raw.buf_mut())
+                .await
+        } else {
+            self.i2c
+                .read_register(self.state.addr, ptr, raw.buf_mut())
+                .await
+        };
+        self.state.read_done(ptr, res.is_ok());
+        res.map_err(Error::I2c)?;
+        Ok(reg)
+    }
+
+    /// Fails with `Error::Locked` if a lock bit would make the device ignore writing `reg`.
+    pub async fn write_register<R: prelude::Write>(
+        &mut self,
+        reg: R,
+    ) -> Result<(), Error<I2C::Error>> {
+        let raw = reg.register();
+        if self.state.needs_lock_check(raw) {
+            let current = self.read_configuration().await?;
+            LockState::check_write(&current, raw).map_err(Error::Locked)?;
+        }
+        let res = self
+            .i2c
+            .write_register(self.state.addr, raw.get_ptr(), raw.get_buf())
+            .await;
+        self.state.write_done(raw, res.is_ok());
+        res.map_err(Error::I2c)
+    }
+
+    /// Also updates the cached lock state.
+    pub async fn read_configuration(&mut self) -> Result<ConfigurationRegister, Error<I2C::Error>> {
+        let reg = self.read_register(reg_conf::new()).await?;
+        self.state.config_read(&reg);
+        Ok(reg)
+    }
+
+    /// Reads the current lock bits from the device.
+    pub async fn lock_state(&mut self) -> Result<LockState, Error<I2C::Error>> {
+        let reg = self.read_configuration().await?;
+        Ok(LockState::from_register(&reg))
+    }
+
+    /// Locks tUPPER and tLOWER, and most configuration bits, until the next power-on reset.
+    pub async fn lock_window(&mut self) -> Result<(), Error<I2C::Error>> {
+        let mut reg = self.read_configuration().await?;
+        reg.set_window_lock(WindowLock::Locked);
+        self.write_register(reg).await
+    }
+
+    /// Locks tCRIT, and most configuration bits, until the next power-on reset.
+    pub async fn lock_critical(&mut self) -> Result<(), Error<I2C::Error>> {
+        let mut reg = self.read_configuration().await?;
+        reg.set_critical_lock(CriticalLock::Locked);
+        self.write_register(reg).await
+    }
+
+    /// Writes the whole configuration at once and verifies it by reading it back.
+    ///
+    /// Fails with `Error::ConfigNotApplied` listing the fields the device did not accept.
+    pub async fn apply_config(&mut self, config: &ConfigBuilder) -> Result<(), Error<I2C::Error>> {
+        self.write_register(config.build()).await?;
+        let actual = self.read_configuration().await?;
+        config
+            .check_applied(&actual)
+            .map_err(Error::ConfigNotApplied)
+    }
+
+    /// Writes tUPPER, returns the value stored after rounding down to 0.25 °C.
+    pub async fn set_alert_upper(&mut self, temp: Celsius) -> Result<Celsius, Error<I2C::Error>> {
+        self.set_limit(Limit::Upper, temp).await
+    }
+
+    /// Writes tLOWER, returns the value stored after rounding down to 0.25 °C.
+    pub async fn set_alert_lower(&mut self, temp: Celsius) -> Result<Celsius, Error<I2C::Error>> {
+        self.set_limit(Limit::Lower, temp).await
+    }
+
+    /// Writes tCRIT, returns the value stored after rounding down to 0.25 °C.
+    pub async fn set_alert_critical(
+        &mut self,
+        temp: Celsius,
+    ) -> Result<Celsius, Error<I2C::Error>> {
+        self.set_limit(Limit::Critical, temp).await
+    }
+
+    async fn set_limit(
+        &mut self,
+        limit: Limit,
+        temp: Celsius,
+    ) -> Result<Celsius, Error<I2C::Error>> {
+        let reg = limit.encode(temp)?;
+        self.write_register(reg).await?;
+        Ok(reg.get_celsius(ResolutionVal::Deg_0_25C))
+    }
+
+    async fn read_limit(&mut self, limit: Limit) -> Result<Celsius, Error<I2C::Error>> {
+        let reg = self.read_register(limit.register()).await?;
+        Ok(reg.get_celsius(ResolutionVal::Deg_0_25C))
+    }
+
+    /// Programs all alert limits and the hysteresis, and enables the alert output.
+    ///
+    /// The limits are validated by `AlertWindow::new()` before anything is written, and verified
+    /// by reading them back afterwards. Fails with `Error::Locked` without writing anything if
+    /// either lock bit is set.
+    pub async fn configure_alert_window(
+        &mut self,
+        lower: Celsius,
+        upper: Celsius,
+        critical: Celsius,
+        hysteresis: Hysteresis,
+    ) -> Result<AlertWindow, Error<I2C::Error>> {
+        let window = AlertWindow::new(lower, upper, critical, hysteresis)?;
+
+        let mut conf = self.read_configuration().await?;
+        LockState::from_register(&conf)
+            .check_limits()
+            .map_err(Error::Locked)?;
+        for limit in Limit::ALL {
+            self.set_limit(limit, window.limit(limit)).await?;
+        }
+        window.configure(&mut conf);
+        self.write_register(conf).await?;
+
+        for limit in Limit::ALL {
+            if self.read_limit(limit).await? != window.limit(limit) {
+                return Err(Error::LimitNotApplied(limit));
+            }
+        }
+        Ok(window)
+    }
+
+    /// Reads the alert flags and clears the interrupt, if the alert output is in interrupt mode.
+    ///
+    /// The interrupt is not cleared while TA ≥ tCRIT, the output then stays asserted until the
+    /// temperature drops below tCRIT - hysteresis.
+    pub async fn service_alert(&mut self) -> Result<AlertEvent, Error<I2C::Error>> {
+        let temp = self.read_temperature().await?;
+        let mut event = AlertEvent::from_register(&temp, self.state.resolution);
+
+        let conf = self.read_configuration().await?;
+        if let Some(clear) = alert::interrupt_clear(conf, &event) {
+            self.write_register(clear).await?;
+            event.cleared = true;
+        }
+        Ok(event)
+    }
+
+    /// Turns the alert output into a change detector, asserted once the temperature moved by more
+    /// than `delta`.
+    ///
+    /// Enables interrupt-mode alerts and centres tLOWER/tUPPER on the current temperature, see
+    /// `recenter_wake_on_change()`. tCRIT is left as it is, it should stay above the temperatures
+    /// the window can move to.
+    pub async fn enable_wake_on_change<D: DelayNs>(
+        &mut self,
+        delta: Celsius,
+        delay: &mut D,
+    ) -> Result<ChangeWindow, Error<I2C::Error>> {
+        let mut conf = self.read_configuration().await?;
+        ChangeWindow::configure(&mut conf);
+        self.write_register(conf).await?;
+        self.recenter_wake_on_change(delta, delay).await
+    }
+
+    /// Re-centres the window of `enable_wake_on_change()` on the current temperature and clears
+    /// the interrupt, call it on each alert.
+    ///
+    /// The device only compares against the new limits at its next conversion, so the interrupt is
+    /// cleared after waiting the typical conversion time of the cached resolution. Clearing it
+    /// earlier would let that conversion raise it again.
+    pub async fn recenter_wake_on_change<D: DelayNs>(
+        &mut self,
+        delta: Celsius,
+        delay: &mut D,
+    ) -> Result<ChangeWindow, Error<I2C::Error>> {
+        let temp = self.read_celsius().await?;
+        let window = ChangeWindow::around(temp, delta)?;
+        self.set_limit(Limit::Lower, window.lower).await?;
+        self.set_limit(Limit::Upper, window.upper).await?;
+
+        // moving the limits across the temperature can raise another interrupt
+        delay
+            .delay_ms(self.state.resolution.conversion_time_ms())
+            .await;
+        let mut conf = self.read_configuration().await?;
+        conf.set_interrupt_clear(InterruptClear::ClearInterruptOutput);
+        self.write_register(conf).await?;
+        Ok(window)
+    }
+
+    pub async fn read_device_id(&mut self) -> Result<DeviceIdRegister, Error<I2C::Error>> {
+        self.read_register(reg_device_id::new()).await
+    }
+
+    pub async fn read_manufacturer_id(
+        &mut self,
+    ) -> Result<ManufacturerIdRegister, Error<I2C::Error>> {
+        self.read_register(reg_manuf_id::new()).await
+    }
+
+    /// Also updates the cached resolution, see `resolution()`.
+    pub async fn read_resolution(&mut self) -> Result<ResolutionRegister, Error<I2C::Error>> {
+        let reg = self.read_register(reg_res::new()).await?;
+        self.state.resolution_read(&reg);
+        Ok(reg)
+    }
+
+    /// Writes the resolution register and updates the cached resolution.
+    pub async fn set_resolution(&mut self, res: ResolutionVal) -> Result<(), Error<I2C::Error>> {
+        let mut reg = reg_res::new();
+        reg.set_resolution(res);
+        self.write_register(reg).await
+    }
+
+    /// Reads the resolution from the device and caches it.
+    pub async fn sync_resolution(&mut self) -> Result<ResolutionVal, Error<I2C::Error>> {
+        let reg = self.read_register(reg_res::new()).await?;
+        self.state.resolution = reg.get_resolution()?;
+        Ok(self.state.resolution)
+    }
+
+    /// Resolution last read from or written to the device, the power-up default until then.
+    pub fn resolution(&self) -> ResolutionVal {
+        self.state.resolution
+    }
+
+    /// Read temperature register. Its double-buffered so no wait required.
+    pub async fn read_temperature(&mut self) -> Result<TemperatureRegister, Error<I2C::Error>> {
+        self.read_register(reg_temp::new()).await
+    }
+
+    /// Reads the temperature, decoded with the cached resolution.
+    pub async fn read_celsius(&mut self) -> Result<Celsius, Error<I2C::Error>> {
+        let reg = self.read_temperature().await?;
+        Ok(reg.get_celsius(self.state.resolution))
+    }
+
+    /// Wakes the device from shutdown for a single conversion and shuts it down again.
+    ///
+    /// Waits the typical conversion time of the cached resolution. Fails with `Error::Locked`
+    /// before waking the device if a lock bit would prevent it from entering shutdown again.
+    pub async fn measure_once<D: DelayNs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<Celsius, Error<I2C::Error>> {
+        let mut conf = self.read_configuration().await?;
+        LockState::check_one_shot(&conf).map_err(Error::Locked)?;
+
+        conf.set_shutdown_mode(ShutdownMode::Continuous);
+        self.write_register(conf).await?;
+        delay
+            .delay_ms(self.state.resolution.conversion_time_ms())
+            .await;
+        let temp = self.read_celsius().await;
+
+        conf.set_shutdown_mode(ShutdownMode::Shutdown);
+        self.write_register(conf).await?;
+        temp
+    }
+
+    /// Like `read_celsius()`, rounded to the nearest m°C for 0.0625 °C resolution.
+    pub async fn read_millicelsius(&mut self) -> Result<i32, Error<I2C::Error>> {
+        Ok(self.read_celsius().await?.to_millicelsius())
+    }
+
+    pub async fn read_alert_critical(
+        &mut self,
+    ) -> Result<CriticalLimitRegister, Error<I2C::Error>> {
+        self.read_register(reg_temp_alert_crit::new()).await
+    }
+
+    pub async fn read_alert_lower(&mut self) -> Result<LowerLimitRegister, Error<I2C::Error>> {
+        self.read_register(reg_temp_alert_lower::new()).await
+    }
+
+    pub async fn read_alert_upper(&mut self) -> Result<UpperLimitRegister, Error<I2C::Error>> {
+        self.read_register(reg_temp_alert_upper::new()).await
+    }
+
+    /// Reads all registers at once, in a single transaction on an I2C bus.
+    ///
+    /// Also updates the cached resolution and lock state.
+    pub async fn read_all(&mut self) -> Result<RegisterSnapshot, Error<I2C::Error>> {
+        let mut raw = RawRegisters::default();
+        let res = self
+            .i2c
+            .read_registers(self.state.addr, &mut raw.batch())
+            .await;
+        self.state.read_all_done(&raw, res)
+    }
+}
+
+/// Finds all MCP9808 on the bus, see `address::scan()`.
+pub async fn scan<I2C>(i2c: &mut I2C) -> Result<Found, Error<I2C::Error>>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    let mut found = Found::default();
+    let mut dev = MCP9808::new(i2c);
+    for addr in SlaveAddress::all() {
+        dev.set_address(addr);
+        match dev.verify_identity().await {
+            Ok(()) => found.insert(addr),
+            Err(err) if address::is_absent(&err) => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(found)
+}
+
+/// MCP9808 together with the input pin wired to its ALERT output, see `MCP9808::with_alert_pin()`.
+pub struct WithAlertPin<I2C, P> {
+    dev: MCP9808<I2C>,
+    pin: P,
+    /// polarity last read from the device
+    polarity: AlertPolarity,
+}
+
+impl<I2C, P> WithAlertPin<I2C, P>
+where
+    I2C: RegisterInterface,
+    P: ErrorType,
+{
+    /// The driver, call `sync_polarity()` after changing the alert polarity through it.
+    pub fn device(&mut self) -> &mut MCP9808<I2C> {
+        &mut self.dev
+    }
+
+    /// Polarity the pin level is interpreted with.
+    pub fn polarity(&self) -> AlertPolarity {
+        self.polarity
+    }
+
+    /// Reads the alert polarity from the device.
+    pub async fn sync_polarity(&mut self) -> Result<AlertPolarity, Error<I2C::Error>> {
+        self.polarity = self.dev.read_configuration().await?.get_alert_polarity();
+        Ok(self.polarity)
+    }
+
+    /// Whether the ALERT output is asserted, without any bus access.
+    pub fn is_alert_asserted(&mut self) -> Result<bool, Error<I2C::Error>>
+    where
+        P: InputPin,
+    {
+        let high = self.pin.is_high().map_err(|e| Error::Pin(e.kind()))?;
+        Ok(self.polarity.is_asserted(high))
+    }
+
+    /// Waits until the ALERT output gets asserted, then services the alert.
+    ///
+    /// See `MCP9808::service_alert()`, the interrupt is cleared if possible. Waits for the edge
+    /// from inactive to active, so a comparator-mode output that stays asserted is not reported
+    /// again. An alert already asserted when called is only seen through `is_alert_asserted()`.
+    pub async fn wait_for_alert(&mut self) -> Result<AlertEvent, Error<I2C::Error>>
+    where
+        P: Wait,
+    {
+        match self.polarity {
+            AlertPolarity::ActiveLow => self.pin.wait_for_falling_edge().await,
+            AlertPolarity::ActiveHigh => self.pin.wait_for_rising_edge().await,
+        }
+        .map_err(|e| Error::Pin(e.kind()))?;
+        self.dev.service_alert().await
+    }
+
+    /// release resources
+    pub fn free(self) -> (MCP9808<I2C>, P) {
+        (self.dev, self.pin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reg_conf::Lock;
+    use crate::reg_res::{Resolution, ResolutionVal};
+    use crate::reg_temp_generic::ReadableTempRegister;
+    use embassy_futures::block_on;
+    use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    const ADDR: u8 = 0b1_1000;
+
+    #[test]
+    fn read_temperature() {
+        let expectations = [Transaction::write_read(
+            ADDR,
+            vec![0b0101],
+            vec![0b00000001, 0b10010100],
+        )];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+
+        let temp = block_on(dev.read_temperature()).unwrap();
+        assert_eq!(temp.get_milli_celsius(ResolutionVal::Deg_0_125C), 25250);
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn read_resolution() {
+        let expectations = [Transaction::write_read(ADDR, vec![0b1000], vec![0b11])];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+
+        let res = block_on(dev.read_resolution()).unwrap();
+        assert_eq!(res.get_resolution().unwrap(), ResolutionVal::Deg_0_0625C);
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn read_celsius_with_cached_resolution() {
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0b1000], vec![0b00]),
+            Transaction::write_read(ADDR, vec![0b0101], vec![0b00000001, 0b10011111]),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+
+        assert_eq!(
+            block_on(dev.sync_resolution()).unwrap(),
+            ResolutionVal::Deg_0_5C
+        );
+        assert_eq!(block_on(dev.read_millicelsius()).unwrap(), 25500);
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn measure_once() {
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0b1000], vec![0b01]),
+            Transaction::write_read(ADDR, vec![0b0001], vec![0b0000_0001, 0b0000_0000]),
+            Transaction::write(ADDR, vec![0b0001, 0b0000_0000, 0b0000_0000]),
+            Transaction::write_read(ADDR, vec![0b0101], vec![0b1111_1111, 0b1001_1100]),
+            Transaction::write(ADDR, vec![0b0001, 0b0000_0001, 0b0000_0000]),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_ms(65)]);
+
+        block_on(dev.sync_resolution()).unwrap();
+        let temp = block_on(dev.measure_once(&mut delay)).unwrap();
+        assert_eq!(temp, Celsius::from_millicelsius(-6_250));
+
+        dev.free().done();
+        delay.done();
+    }
+
+    #[test]
+    fn measure_once_locked() {
+        let expectations = [Transaction::write_read(
+            ADDR,
+            vec![0b0001],
+            vec![0b0000_0001, 0b1000_0000],
+        )];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+        let mut delay = CheckedDelay::new(&[]);
+
+        let err = block_on(dev.measure_once(&mut delay)).err();
+        assert!(matches!(err, Some(Error::Locked(Lock::Critical))));
+
+        dev.free().done();
+        delay.done();
+    }
+
+    #[test]
+    fn wait_for_alert() {
+        use embedded_hal_mock::eh1::digital::{
+            Edge, Mock as PinMock, Transaction as PinTransaction,
+        };
+
+        let expectations = [
+            // active-high, comparator mode
+            Transaction::write_read(ADDR, vec![0b0001], vec![0x00, 0x0a]),
+            // 46 °C, TA ≥ tCRIT
+            Transaction::write_read(ADDR, vec![0b0101], vec![0xc2, 0xe0]),
+            Transaction::write_read(ADDR, vec![0b0001], vec![0x00, 0x1a]),
+        ];
+        // waits for the edge, a comparator output that is still asserted is not a new alert
+        let pin = PinMock::new(&[PinTransaction::wait_for_edge(Edge::Rising)]);
+        let mut dev = block_on(MCP9808::new(Mock::new(&expectations)).with_alert_pin(pin)).unwrap();
+        assert_eq!(dev.polarity(), AlertPolarity::ActiveHigh);
+
+        let event = block_on(dev.wait_for_alert()).unwrap();
+        assert!(event.critical && !event.cleared);
+
+        let (dev, mut pin) = dev.free();
+        dev.free().done();
+        pin.done();
+    }
+
+    #[test]
+    fn pointer_cache() {
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0b0101], vec![0x01, 0x94]),
+            Transaction::read(ADDR, vec![0x01, 0x94]),
+            Transaction::write(ADDR, vec![0b1000, 0b01]),
+            Transaction::write_read(ADDR, vec![0b0101], vec![0x01, 0x94]),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+        dev.set_pointer_cache(true);
+
+        assert_eq!(block_on(dev.read_millicelsius()).unwrap(), 25_250);
+        assert_eq!(block_on(dev.read_millicelsius()).unwrap(), 25_250);
+        block_on(dev.set_resolution(ResolutionVal::Deg_0_25C)).unwrap();
+        assert_eq!(block_on(dev.read_millicelsius()).unwrap(), 25_250);
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn read_all() {
+        let registers = [
+            (0b0001, vec![0x00, 0x00]),
+            (0b0010, vec![0x00, 0x00]),
+            (0b0011, vec![0x00, 0x00]),
+            (0b0100, vec![0x00, 0x00]),
+            (0b0101, vec![0x01, 0x94]),
+            (0b0110, vec![0x00, 0x54]),
+            (0b0111, vec![0x04, 0x00]),
+            (0b1000, vec![0b11]),
+        ];
+        let mut expectations = vec![Transaction::transaction_start(ADDR)];
+        for (ptr, data) in registers {
+            expectations.push(Transaction::write(ADDR, vec![ptr]));
+            expectations.push(Transaction::read(ADDR, data));
+        }
+        expectations.push(Transaction::transaction_end(ADDR));
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+
+        let snapshot = block_on(dev.read_all()).unwrap();
+        assert_eq!(snapshot.temperature, Celsius::from_millicelsius(25_250));
+        assert_eq!(snapshot.resolution, ResolutionVal::Deg_0_0625C);
+
+        dev.free().done();
+    }
+}
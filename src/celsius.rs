@@ -0,0 +1,209 @@
+//! Fixed-point temperature value
+
+use core::ops::{Add, Neg, Sub};
+
+/// sixteenths of a degree per degree, the finest resolution of the sensor
+const SIXTEENTHS: i32 = 16;
+
+/// Temperature in degree celsius, stored exactly as 1/16 °C steps in two's complement.
+///
+/// This is the native format of the sensor, so register values convert without loss.
+/// Arithmetic saturates instead of overflowing, which makes it usable for deltas as well.
+///
+/// # Example
+///
+/// ```
+/// use mcp9808::celsius::Celsius;
+///
+/// let temp = Celsius::from_sixteenths(-3);
+/// assert_eq!(temp.to_microcelsius(), -187_500);
+/// assert_eq!(temp.to_millicelsius(), -188);
+///
+/// let delta = Celsius::from_millicelsius(25_250) - Celsius::from_degrees(20);
+/// assert_eq!(delta, Celsius::from_millicelsius(5_250));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Celsius(i16);
+
+impl Celsius {
+    pub const ZERO: Celsius = Celsius(0);
+    pub const MIN: Celsius = Celsius(i16::MIN);
+    pub const MAX: Celsius = Celsius(i16::MAX);
+
+    /// from raw 1/16 °C steps
+    pub const fn from_sixteenths(raw: i16) -> Self {
+        Celsius(raw)
+    }
+
+    /// raw 1/16 °C steps
+    pub const fn as_sixteenths(self) -> i16 {
+        self.0
+    }
+
+    /// whole degrees, saturating
+    pub fn from_degrees(deg: i16) -> Self {
+        Celsius(deg.saturating_mul(SIXTEENTHS as i16))
+    }
+
+    /// rounds to the nearest 1/16 °C, saturating
+    pub fn from_millicelsius(mc: i32) -> Self {
+        Self::from_scaled(mc as i64, 1000)
+    }
+
+    /// rounds to the nearest 1/16 °C, saturating
+    pub fn from_centicelsius(cc: i32) -> Self {
+        Self::from_scaled(cc as i64, 100)
+    }
+
+    /// exact
+    pub fn to_microcelsius(self) -> i32 {
+        self.0 as i32 * (1_000_000 / SIXTEENTHS)
+    }
+
+    /// exact for multiples of 1/8 °C, otherwise rounded to the nearest value
+    pub fn to_millicelsius(self) -> i32 {
+        div_round(self.to_microcelsius() as i64, 1000) as i32
+    }
+
+    /// exact for multiples of 1/4 °C, otherwise rounded to the nearest value
+    pub fn to_centicelsius(self) -> i32 {
+        div_round(self.to_microcelsius() as i64, 10_000) as i32
+    }
+
+    /// rounded to the nearest value
+    pub fn to_millifahrenheit(self) -> i32 {
+        div_round(self.to_microcelsius() as i64 * 9, 5 * 1000) as i32 + 32_000
+    }
+
+    /// rounded to the nearest value
+    pub fn to_millikelvin(self) -> i32 {
+        self.to_millicelsius() + 273_150
+    }
+
+    /// rounds to the nearest 1/16 °C, saturating; NaN becomes 0 °C
+    #[cfg(feature = "with_floating_point")]
+    pub fn from_f32(val: f32) -> Self {
+        let scaled = val * SIXTEENTHS as f32;
+        // float to int casts saturate
+        let rounded = if scaled < 0.0 {
+            scaled - 0.5
+        } else {
+            scaled + 0.5
+        };
+        Celsius(rounded as i16)
+    }
+
+    /// exact
+    #[cfg(feature = "with_floating_point")]
+    pub fn to_f32(self) -> f32 {
+        f32::from(self.0) / SIXTEENTHS as f32
+    }
+
+    #[cfg(feature = "with_floating_point")]
+    pub fn to_fahrenheit(self) -> f32 {
+        self.to_f32() * 1.8 + 32.0
+    }
+
+    #[cfg(feature = "with_floating_point")]
+    pub fn to_kelvin(self) -> f32 {
+        self.to_f32() + 273.15
+    }
+
+    pub fn checked_add(self, rhs: Celsius) -> Option<Celsius> {
+        self.0.checked_add(rhs.0).map(Celsius)
+    }
+
+    pub fn checked_sub(self, rhs: Celsius) -> Option<Celsius> {
+        self.0.checked_sub(rhs.0).map(Celsius)
+    }
+
+    pub fn abs(self) -> Celsius {
+        Celsius(self.0.saturating_abs())
+    }
+
+    fn from_scaled(val: i64, per_degree: i64) -> Self {
+        let raw = div_round(val * SIXTEENTHS as i64, per_degree);
+        Celsius(raw.clamp(i16::MIN as i64, i16::MAX as i64) as i16)
+    }
+}
+
+impl Add for Celsius {
+    type Output = Celsius;
+
+    fn add(self, rhs: Celsius) -> Celsius {
+        Celsius(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl Sub for Celsius {
+    type Output = Celsius;
+
+    fn sub(self, rhs: Celsius) -> Celsius {
+        Celsius(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl Neg for Celsius {
+    type Output = Celsius;
+
+    fn neg(self) -> Celsius {
+        Celsius(self.0.saturating_neg())
+    }
+}
+
+/// integer division rounding half away from zero, `div` must be positive
+fn div_round(val: i64, div: i64) -> i64 {
+    if val < 0 {
+        (val - div / 2) / div
+    } else {
+        (val + div / 2) / div
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        let temp = Celsius::from_sixteenths(0b1_1001_0100); // 25.25 °C
+        assert_eq!(temp.to_microcelsius(), 25_250_000);
+        assert_eq!(temp.to_millicelsius(), 25_250);
+        assert_eq!(temp.to_centicelsius(), 2_525);
+        assert_eq!(temp.to_millifahrenheit(), 77_450);
+        assert_eq!(temp.to_millikelvin(), 298_400);
+        assert_eq!(Celsius::from_millicelsius(25_250), temp);
+        assert_eq!(Celsius::from_centicelsius(2_525), temp);
+
+        let sixteenth = Celsius::from_sixteenths(1);
+        assert_eq!(sixteenth.to_microcelsius(), 62_500);
+        assert_eq!(sixteenth.to_millicelsius(), 63);
+        assert_eq!((-sixteenth).to_millicelsius(), -63);
+        assert_eq!(Celsius::from_millicelsius(62), sixteenth);
+        assert_eq!(Celsius::from_millicelsius(-63), -sixteenth);
+    }
+
+    #[cfg(feature = "with_floating_point")]
+    #[test]
+    fn float_conversions() {
+        let temp = Celsius::from_f32(-10.0625);
+        assert_eq!(temp.as_sixteenths(), -161);
+        assert_eq!(temp.to_f32(), -10.0625);
+        assert_eq!(Celsius::from_degrees(100).to_fahrenheit(), 212.0);
+        assert_eq!(Celsius::from_f32(f32::NAN), Celsius::ZERO);
+        assert_eq!(Celsius::from_f32(1e9), Celsius::MAX);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = Celsius::from_degrees(20);
+        let b = Celsius::from_millicelsius(22_500);
+        assert!(a < b);
+        assert_eq!(b - a, Celsius::from_millicelsius(2_500));
+        assert_eq!(a - b, Celsius::from_millicelsius(-2_500));
+        assert_eq!((a - b).abs(), b - a);
+        assert_eq!(Celsius::MAX + a, Celsius::MAX);
+        assert_eq!(Celsius::MAX.checked_add(a), None);
+        assert_eq!(Celsius::MIN.checked_sub(a), None);
+    }
+}
@@ -0,0 +1,170 @@
+//! Driver state shared by the blocking and the async driver
+//!
+//! Both drivers only differ in how they access the bus. What they know about the device, and how
+//! every read and write changes that, is decided here from register values alone.
+
+use crate::address::SlaveAddress;
+use crate::error::Error;
+use crate::reg::Register;
+use crate::reg_conf::{self, ConfigurationRegister, LockState};
+use crate::reg_device_id::{DeviceId, DeviceIdRegister};
+use crate::reg_manuf_id::{ManufacturerId, ManufacturerIdRegister};
+use crate::reg_res::{self, Resolution, ResolutionRegister, ResolutionVal};
+use crate::snapshot::{RawRegisters, RegisterSnapshot};
+
+/// What the driver knows about one device, without the bus
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct DeviceState {
+    pub(crate) addr: u8,
+    /// resolution last read from or written to the device
+    pub(crate) resolution: ResolutionVal,
+    /// device revision, known after the identity was verified
+    pub(crate) revision: Option<u8>,
+    /// lock bits last read from or written to the device
+    locks: Option<LockState>,
+    /// register pointer last set on the device, `None` if unknown
+    pointer: Option<u8>,
+    /// whether reads skip the pointer write when it is already set
+    cache_pointer: bool,
+}
+
+impl DeviceState {
+    /// device at `addr`, nothing known about it yet
+    pub(crate) fn new(addr: SlaveAddress) -> Self {
+        DeviceState {
+            addr: addr.into(),
+            resolution: ResolutionVal::Deg_0_0625C,
+            revision: None,
+            locks: None,
+            pointer: None,
+            cache_pointer: false,
+        }
+    }
+
+    pub(crate) fn set_address(&mut self, addr: SlaveAddress) -> u8 {
+        self.addr = addr.into();
+        self.pointer = None;
+        self.addr
+    }
+
+    pub(crate) fn set_pointer_cache(&mut self, enabled: bool) {
+        self.cache_pointer = enabled;
+        self.pointer = None;
+    }
+
+    /// whether reading the register at `ptr` can skip writing the pointer
+    pub(crate) fn is_pointing_to(&self, ptr: u8) -> bool {
+        self.cache_pointer && self.pointer == Some(ptr)
+    }
+
+    /// records a read of the register at `ptr`, a failed one leaves the pointer unknown
+    pub(crate) fn read_done(&mut self, ptr: u8, ok: bool) {
+        self.pointer = ok.then_some(ptr);
+    }
+
+    /// whether writing `reg` needs the lock bits read from the device first
+    pub(crate) fn needs_lock_check(&self, reg: &Register) -> bool {
+        LockState::is_lockable(reg.get_ptr()) && self.locks != Some(LockState::UNLOCKED)
+    }
+
+    /// records a write of `reg`, the cached resolution and lock state only follow a successful one
+    pub(crate) fn write_done(&mut self, reg: &Register, ok: bool) {
+        self.pointer = None;
+        if !ok {
+            return;
+        }
+        if let Some(res) = reg_res::resolution_of(reg) {
+            self.resolution = res;
+        }
+        if let Some(locks) = reg_conf::locks_of(reg) {
+            self.locks = Some(locks);
+        }
+    }
+
+    pub(crate) fn config_read(&mut self, reg: &ConfigurationRegister) {
+        self.locks = Some(LockState::from_register(reg));
+    }
+
+    /// keeps the cached resolution if `reg` holds unimplemented bits
+    pub(crate) fn resolution_read(&mut self, reg: &ResolutionRegister) {
+        if let Ok(res) = reg.get_resolution() {
+            self.resolution = res;
+        }
+    }
+
+    /// Records the revision if `reg` holds the MCP9808 device ID.
+    pub(crate) fn identify<E>(&mut self, reg: &DeviceIdRegister) -> Result<(), Error<E>> {
+        if !reg.is_valid_device() {
+            return Err(Error::WrongDevice {
+                id: reg.get_device_id(),
+                rev: reg.get_device_rev(),
+            });
+        }
+        self.revision = Some(reg.get_device_rev());
+        Ok(())
+    }
+
+    /// Decodes the registers `read_registers()` filled in and caches resolution and lock state.
+    pub(crate) fn read_all_done<E>(
+        &mut self,
+        raw: &RawRegisters,
+        res: Result<(), E>,
+    ) -> Result<RegisterSnapshot, Error<E>> {
+        self.pointer = res.is_ok().then_some(RawRegisters::LAST_POINTER);
+        res.map_err(Error::I2c)?;
+
+        let snapshot = raw.decode(self.resolution);
+        self.resolution = snapshot.resolution;
+        self.locks = Some(LockState::from_register(&snapshot.config.build()));
+        Ok(snapshot)
+    }
+}
+
+/// Fails unless `reg` holds the manufacturer ID of Microchip.
+pub(crate) fn check_manufacturer<E>(reg: &ManufacturerIdRegister) -> Result<(), Error<E>> {
+    if !reg.is_valid_manufacturer() {
+        return Err(Error::WrongManufacturer(reg.get_manufacturer_id()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Read;
+    use crate::reg_conf::{Configuration, WindowLock};
+    use crate::reg_temp_alert_upper;
+
+    #[test]
+    fn bookkeeping() {
+        let mut state = DeviceState::new(SlaveAddress::Default);
+        let upper = *reg_temp_alert_upper::new().register();
+        assert!(state.needs_lock_check(&upper));
+
+        // pointer is only trusted with the cache enabled, and after a successful read
+        state.read_done(0b0101, true);
+        assert!(!state.is_pointing_to(0b0101));
+        state.set_pointer_cache(true);
+        state.read_done(0b0101, true);
+        assert!(state.is_pointing_to(0b0101));
+        state.read_done(0b0101, false);
+        assert!(!state.is_pointing_to(0b0101));
+
+        let mut conf = reg_conf::new();
+        state.config_read(&conf);
+        assert!(!state.needs_lock_check(&upper));
+        conf.set_window_lock(WindowLock::Locked);
+        state.write_done(conf.register(), false);
+        assert!(!state.needs_lock_check(&upper));
+        state.write_done(conf.register(), true);
+        assert!(state.needs_lock_check(&upper));
+
+        let mut res = reg_res::new();
+        res.set_resolution(ResolutionVal::Deg_0_25C);
+        state.write_done(res.register(), true);
+        assert_eq!(state.resolution, ResolutionVal::Deg_0_25C);
+        res.register_mut().set_msb(0b100);
+        state.resolution_read(&res);
+        assert_eq!(state.resolution, ResolutionVal::Deg_0_25C);
+    }
+}
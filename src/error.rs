@@ -1,12 +1,77 @@
+use crate::alert::Limit;
+use crate::reg_conf::{ConfigDiff, Lock};
+use embedded_hal::digital::ErrorKind;
+
 /// All possible errors in this crate
 #[derive(Debug)]
 pub enum Error<E> {
     /// I2C bus error
     I2c(E),
+    /// register size is not 1 or 2 bytes
     RegisterSizeMismatch(u8),
+    /// register pointer is reserved
+    InvalidPointer(u8),
+    /// bit offset beyond the register size
+    BitOutOfBounds(usize),
+    /// temperature outside the range the registers can represent
+    OutOfRange,
+    /// register contains bits the datasheet says can not be set
+    InvalidRegisterValue(u16),
+    /// manufacturer ID is not the one of Microchip
+    WrongManufacturer(u16),
+    /// device ID is not the one of the MCP9808
+    WrongDevice { id: u8, rev: u8 },
+    /// configuration read back differs from the one written
+    ConfigNotApplied(ConfigDiff),
+    /// the device would ignore the write because of a lock bit
+    Locked(Lock),
+    /// alert limits are not ordered lower < upper <= critical
+    InvalidAlertWindow,
+    /// alert limit is not a multiple of 0.25 °C
+    LimitNotQuantized(Limit),
+    /// alert limit read back differs from the one written
+    LimitNotApplied(Limit),
+    /// error reading the pin wired to the ALERT output
+    Pin(ErrorKind),
+    /// I2C multiplexer channel is not 0 - 7
+    InvalidChannel(u8),
+    /// more sensors than the eight addresses the device can be strapped to
+    TooManySensors(usize),
+    /// two sensors share the same bus address
+    DuplicateAddress(u8),
 }
-impl<E> From<E> for Error<E> {
-    fn from(other: E) -> Self {
-        Error::I2c(other)
+
+/// Errors of register values, temperatures and alert limits, independent of the bus.
+///
+/// Converts into the `Error` variant of the same name, so `?` works in code returning `Error<E>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueError {
+    /// register size is not 1 or 2 bytes
+    RegisterSizeMismatch(u8),
+    /// register pointer is reserved
+    InvalidPointer(u8),
+    /// bit offset beyond the register size
+    BitOutOfBounds(usize),
+    /// temperature outside the range the registers can represent
+    OutOfRange,
+    /// register contains bits the datasheet says can not be set
+    InvalidRegisterValue(u16),
+    /// alert limits are not ordered lower < upper <= critical
+    InvalidAlertWindow,
+    /// alert limit is not a multiple of 0.25 °C
+    LimitNotQuantized(Limit),
+}
+
+impl<E> From<ValueError> for Error<E> {
+    fn from(err: ValueError) -> Self {
+        match err {
+            ValueError::RegisterSizeMismatch(len) => Error::RegisterSizeMismatch(len),
+            ValueError::InvalidPointer(ptr) => Error::InvalidPointer(ptr),
+            ValueError::BitOutOfBounds(offset) => Error::BitOutOfBounds(offset),
+            ValueError::OutOfRange => Error::OutOfRange,
+            ValueError::InvalidRegisterValue(val) => Error::InvalidRegisterValue(val),
+            ValueError::InvalidAlertWindow => Error::InvalidAlertWindow,
+            ValueError::LimitNotQuantized(limit) => Error::LimitNotQuantized(limit),
+        }
     }
 }
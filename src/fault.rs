@@ -0,0 +1,234 @@
+//! Fault injection for tests of error handling
+//!
+//! `FaultyI2c` wraps a bus, usually a `sim::SimulatedMcp9808`, and makes scheduled calls fail
+//! the way real hardware does.
+
+use embedded_hal::i2c::{
+    Error as _, ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation, SevenBitAddress,
+};
+
+/// Failure injected into one bus call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// nothing acknowledges the address, the device sees nothing
+    NackAddress,
+    /// the device takes the register pointer, then the data is not acknowledged
+    NackData,
+    /// read bytes are XORed with the mask
+    FlipBits(u8),
+    /// the device stops after the given number of bytes, the rest reads as 0xFF
+    TruncateRead(usize),
+    /// the given number of calls, starting with this one, fail with `ErrorKind::Other` as a bus
+    /// timeout would
+    Hang(usize),
+}
+
+/// I2C wrapper failing the calls of a schedule of `(call index, fault)`, counted from 0.
+///
+/// Calls are passed on with `transaction()`, which the wrapped bus has to support.
+///
+/// # Example
+///
+/// ```
+/// use mcp9808::address::SlaveAddress;
+/// use mcp9808::error::Error;
+/// use mcp9808::fault::{Fault, FaultyI2c};
+/// use mcp9808::sim::SimulatedMcp9808;
+/// use mcp9808::MCP9808;
+///
+/// let sim = SimulatedMcp9808::new(SlaveAddress::Default);
+/// let schedule = [(1, Fault::NackAddress)];
+/// let mut dev = MCP9808::new(FaultyI2c::new(sim, &schedule));
+///
+/// assert!(dev.read_celsius().is_ok());
+/// assert!(matches!(dev.read_celsius(), Err(Error::I2c(_))));
+/// assert!(dev.read_celsius().is_ok());
+/// ```
+#[derive(Debug)]
+pub struct FaultyI2c<'a, I2C> {
+    inner: I2C,
+    schedule: &'a [(usize, Fault)],
+    calls: usize,
+    /// calls left until a hang is over
+    hang: usize,
+}
+
+impl<'a, I2C> FaultyI2c<'a, I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    pub fn new(inner: I2C, schedule: &'a [(usize, Fault)]) -> Self {
+        FaultyI2c {
+            inner,
+            schedule,
+            calls: 0,
+            hang: 0,
+        }
+    }
+
+    /// number of calls so far
+    pub fn calls(&self) -> usize {
+        self.calls
+    }
+
+    /// the wrapped bus
+    pub fn inner(&mut self) -> &mut I2C {
+        &mut self.inner
+    }
+
+    /// release resources
+    pub fn free(self) -> I2C {
+        self.inner
+    }
+
+    fn next_fault(&mut self) -> Option<Fault> {
+        let call = self.calls;
+        self.calls += 1;
+        if self.hang > 0 {
+            self.hang -= 1;
+            return Some(Fault::Hang(self.hang));
+        }
+        let fault = self
+            .schedule
+            .iter()
+            .find(|(at, _)| *at == call)
+            .map(|(_, fault)| *fault)?;
+        if let Fault::Hang(calls) = fault {
+            self.hang = calls.saturating_sub(1);
+        }
+        Some(fault)
+    }
+
+    fn forward(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        self.inner
+            .transaction(address, operations)
+            .map_err(|e| e.kind())
+    }
+}
+
+impl<I2C: ErrorType> ErrorType for FaultyI2c<'_, I2C> {
+    type Error = ErrorKind;
+}
+
+impl<I2C> I2c<SevenBitAddress> for FaultyI2c<'_, I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let reads = |ops: &mut [Operation<'_>], f: &mut dyn FnMut(usize, &mut u8)| {
+            let mut pos = 0;
+            for op in ops.iter_mut() {
+                if let Operation::Read(buf) = op {
+                    for byte in buf.iter_mut() {
+                        f(pos, byte);
+                        pos += 1;
+                    }
+                }
+            }
+        };
+
+        match self.next_fault() {
+            None => self.forward(address, operations),
+            Some(Fault::NackAddress) => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            Some(Fault::NackData) => {
+                if let Some(Operation::Write([ptr, ..])) = operations.first() {
+                    self.forward(address, &mut [Operation::Write(&[*ptr])])?;
+                }
+                Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data))
+            }
+            Some(Fault::FlipBits(mask)) => {
+                self.forward(address, operations)?;
+                reads(operations, &mut |_, byte| *byte ^= mask);
+                Ok(())
+            }
+            Some(Fault::TruncateRead(len)) => {
+                self.forward(address, operations)?;
+                reads(operations, &mut |pos, byte| {
+                    if pos >= len {
+                        *byte = 0xff;
+                    }
+                });
+                Ok(())
+            }
+            Some(Fault::Hang(_)) => Err(ErrorKind::Other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::SlaveAddress;
+    use crate::celsius::Celsius;
+    use crate::error::Error;
+    use crate::reg_conf::{Configuration, ShutdownMode};
+    use crate::sim::SimulatedMcp9808;
+    use crate::MCP9808;
+
+    fn driver(schedule: &[(usize, Fault)]) -> MCP9808<FaultyI2c<'_, SimulatedMcp9808>> {
+        let mut sim = SimulatedMcp9808::new(SlaveAddress::Default);
+        sim.set_temperature(Celsius::from_millicelsius(23_500));
+        MCP9808::new(FaultyI2c::new(sim, schedule))
+    }
+
+    #[test]
+    fn nack() {
+        let dev = driver(&[(0, Fault::NackAddress)]);
+        let err = MCP9808::probe(dev.free(), SlaveAddress::Default).err();
+        assert!(matches!(
+            err,
+            Some(Error::I2c(ErrorKind::NoAcknowledge(
+                NoAcknowledgeSource::Address
+            )))
+        ));
+
+        // the pointer reaches the device, the configuration does not
+        let mut dev = driver(&[(1, Fault::NackData)]);
+        let mut conf = dev.read_configuration().unwrap();
+        conf.set_shutdown_mode(ShutdownMode::Shutdown);
+        let err = dev.write_register(conf).err();
+        assert!(matches!(
+            err,
+            Some(Error::I2c(ErrorKind::NoAcknowledge(
+                NoAcknowledgeSource::Data
+            )))
+        ));
+        let sim = dev.free().free();
+        assert!(!sim.is_shutdown());
+        assert_eq!(sim.pointer(), 0b0001);
+    }
+
+    #[test]
+    fn corrupted_reads() {
+        let mut dev = driver(&[(0, Fault::FlipBits(0x01)), (2, Fault::TruncateRead(1))]);
+        assert!(matches!(
+            dev.verify_identity(),
+            Err(Error::WrongManufacturer(0x0155))
+        ));
+
+        assert_eq!(dev.read_millicelsius().unwrap(), 23_500);
+        // 0x01 followed by 0xff
+        let temp = dev.read_celsius().unwrap();
+        assert_eq!(temp, Celsius::from_sixteenths(0x1ff));
+    }
+
+    #[test]
+    fn hang() {
+        let mut dev = driver(&[(1, Fault::Hang(2))]);
+        assert!(dev.read_celsius().is_ok());
+        assert!(matches!(
+            dev.read_celsius(),
+            Err(Error::I2c(ErrorKind::Other))
+        ));
+        assert!(matches!(
+            dev.read_celsius(),
+            Err(Error::I2c(ErrorKind::Other))
+        ));
+        assert!(dev.read_celsius().is_ok());
+        assert_eq!(dev.free().calls(), 4);
+    }
+}
@@ -0,0 +1,194 @@
+//! Register transport
+//!
+//! The driver only reads and writes registers by pointer. Every embedded-hal I2C bus implements
+//! `RegisterInterface`, other transports like SMBus bridges, USB-I2C dongles or in-memory register
+//! files can implement it themselves.
+
+use core::fmt::Debug;
+use embedded_hal::i2c::{I2c, Operation, SevenBitAddress};
+
+/// access to the registers of the device at a bus address
+pub trait RegisterInterface {
+    type Error: Debug;
+
+    /// reads `buf.len()` bytes from the register at `ptr`
+    fn read_register(&mut self, addr: u8, ptr: u8, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// reads `buf.len()` bytes from the register the pointer was last set to
+    fn read_current_register(&mut self, addr: u8, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// writes `data` to the register at `ptr`
+    fn write_register(&mut self, addr: u8, ptr: u8, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// reads several `(pointer, buffer)` registers, one after another by default
+    fn read_registers(
+        &mut self,
+        addr: u8,
+        regs: &mut [(u8, &mut [u8])],
+    ) -> Result<(), Self::Error> {
+        for (ptr, buf) in regs.iter_mut() {
+            self.read_register(addr, *ptr, buf)?;
+        }
+        Ok(())
+    }
+}
+
+/// registers read per I2C transaction by `read_registers()`
+pub(crate) const BATCH: usize = 8;
+
+/// `ptr` followed by `data` in `buf`, `None` if `data` is longer than a register
+pub(crate) fn write_buf<'a>(buf: &'a mut [u8; 3], ptr: u8, data: &[u8]) -> Option<&'a [u8]> {
+    let (head, payload) = buf.split_first_mut()?;
+    *head = ptr;
+    payload.get_mut(..data.len())?.copy_from_slice(data);
+    buf.get(..=data.len())
+}
+
+/// Fills `ops` with a pointer write and a read per register of `chunk`, at most `BATCH`.
+pub(crate) fn batch_ops<'a, 'b>(
+    ops: &'a mut [Operation<'b>; 2 * BATCH],
+    chunk: &'b mut [(u8, &mut [u8])],
+) -> &'a mut [Operation<'b>] {
+    let len = 2 * chunk.len();
+    for (pair, (ptr, buf)) in ops.chunks_exact_mut(2).zip(chunk.iter_mut()) {
+        if let [write, read] = pair {
+            *write = Operation::Write(core::slice::from_ref(ptr));
+            *read = Operation::Read(buf);
+        }
+    }
+    ops.get_mut(..len).unwrap_or(&mut [])
+}
+
+/// default transport: sets the pointer, then reads or writes in the same transaction
+impl<I2C> RegisterInterface for I2C
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    type Error = I2C::Error;
+
+    fn read_register(&mut self, addr: u8, ptr: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.write_read(addr, &[ptr], buf)
+    }
+
+    fn read_current_register(&mut self, addr: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.read(addr, buf)
+    }
+
+    fn write_register(&mut self, addr: u8, ptr: u8, data: &[u8]) -> Result<(), Self::Error> {
+        match write_buf(&mut [0; 3], ptr, data) {
+            Some(buf) => self.write(addr, buf),
+            // registers are at most 2 bytes, anything longer follows the pointer without a restart
+            None => self.transaction(
+                addr,
+                &mut [Operation::Write(&[ptr]), Operation::Write(data)],
+            ),
+        }
+    }
+
+    /// up to 8 registers per transaction, with a repeated start instead of a stop in between
+    fn read_registers(
+        &mut self,
+        addr: u8,
+        regs: &mut [(u8, &mut [u8])],
+    ) -> Result<(), Self::Error> {
+        for chunk in regs.chunks_mut(BATCH) {
+            let mut ops = core::array::from_fn(|_| Operation::Write(&[]));
+            self.transaction(addr, batch_ops(&mut ops, chunk))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::celsius::Celsius;
+    use crate::reg_res::ResolutionVal;
+    use crate::reg_temp_generic::ReadableTempRegister;
+    use crate::MCP9808;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    /// registers kept in memory, as a bridge with its own register cache would
+    struct RegisterFile {
+        regs: [[u8; 2]; 9],
+        ptr: u8,
+        writes: usize,
+    }
+
+    impl RegisterInterface for RegisterFile {
+        type Error = ();
+
+        fn read_register(&mut self, addr: u8, ptr: u8, buf: &mut [u8]) -> Result<(), ()> {
+            self.ptr = ptr;
+            self.read_current_register(addr, buf)
+        }
+
+        fn read_current_register(&mut self, _addr: u8, buf: &mut [u8]) -> Result<(), ()> {
+            let reg = self.regs.get(self.ptr as usize).ok_or(())?;
+            for (dst, src) in buf.iter_mut().zip(reg) {
+                *dst = *src;
+            }
+            Ok(())
+        }
+
+        fn write_register(&mut self, _addr: u8, ptr: u8, data: &[u8]) -> Result<(), ()> {
+            self.ptr = ptr;
+            let reg = self.regs.get_mut(ptr as usize).ok_or(())?;
+            for (dst, src) in reg.iter_mut().zip(data) {
+                *dst = *src;
+            }
+            self.writes += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn register_file() {
+        let mut regs = [[0u8; 2]; 9];
+        regs[5] = [0x01, 0x94];
+        regs[6] = [0x00, 0x54];
+        regs[7] = [0x04, 0x00];
+        let mut dev = MCP9808::new(RegisterFile {
+            regs,
+            ptr: 0,
+            writes: 0,
+        });
+
+        dev.verify_identity().unwrap();
+        assert_eq!(dev.read_millicelsius().unwrap(), 25_250);
+        dev.set_alert_upper(Celsius::from_degrees(30)).unwrap();
+        let upper = dev.read_alert_upper().unwrap();
+        assert_eq!(
+            upper.get_celsius(ResolutionVal::Deg_0_25C),
+            Celsius::from_degrees(30)
+        );
+
+        // one register after another
+        let snapshot = dev.read_all().unwrap();
+        assert_eq!(snapshot.upper, Celsius::from_degrees(30));
+        assert_eq!(snapshot.device_id, 0x04);
+
+        let file = dev.free();
+        assert_eq!(file.regs[2], [0x01, 0xe0]);
+        assert_eq!(file.writes, 1);
+    }
+
+    #[test]
+    fn i2c() {
+        let expectations = [
+            Transaction::write_read(0x18, vec![0b0101], vec![0x01, 0x94]),
+            Transaction::write(0x18, vec![0b0010, 0x01, 0xe0]),
+            Transaction::transaction_start(0x18),
+            Transaction::write(0x18, vec![0b0010]),
+            Transaction::write(0x18, vec![1, 2, 3]),
+            Transaction::transaction_end(0x18),
+        ];
+        let mut i2c = Mock::new(&expectations);
+        let mut buf = [0u8; 2];
+        i2c.read_register(0x18, 0b0101, &mut buf).unwrap();
+        assert_eq!(buf, [0x01, 0x94]);
+        i2c.write_register(0x18, 0b0010, &[0x01, 0xe0]).unwrap();
+        i2c.write_register(0x18, 0b0010, &[1, 2, 3]).unwrap();
+        i2c.done();
+    }
+}
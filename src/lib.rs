@@ -1,25 +1,57 @@
 #![deny(warnings)]
-#![no_std]
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::indexing_slicing,
+        clippy::unreachable
+    )
+)]
+#![cfg_attr(not(test), no_std)]
 
 extern crate bit_field;
-extern crate cast;
 extern crate embedded_hal;
 
-use crate::error::Error;
-use crate::reg_conf::Configuration;
-use crate::reg_device_id::DeviceId;
-use crate::reg_manuf_id::ManufacturerId;
-use crate::reg_res::Resolution;
-use crate::reg_temp::Temperature;
-use crate::reg_temp_alert_crit::CriticalTemperatureAlert;
-use crate::reg_temp_alert_lower::LowerTemperatureAlert;
-use crate::reg_temp_alert_upper::UpperTemperatureAlert;
 use crate::address::SlaveAddress;
-use embedded_hal::i2c::{I2c, SevenBitAddress};
+use crate::alert::{AlertEvent, AlertWindow, ChangeWindow, Limit};
+use crate::alert_pin::WithAlertPin;
+use crate::celsius::Celsius;
+use crate::device::DeviceState;
+use crate::error::Error;
+use crate::interface::RegisterInterface;
+use crate::reg_conf::{
+    ConfigBuilder, Configuration, ConfigurationRegister, CriticalLock, Hysteresis, InterruptClear,
+    LockState, ShutdownMode, WindowLock,
+};
+use crate::reg_device_id::DeviceIdRegister;
+use crate::reg_manuf_id::ManufacturerIdRegister;
+use crate::reg_res::{Resolution, ResolutionRegister, ResolutionVal};
+use crate::reg_temp::TemperatureRegister;
+use crate::reg_temp_alert_crit::CriticalLimitRegister;
+use crate::reg_temp_alert_lower::LowerLimitRegister;
+use crate::reg_temp_alert_upper::UpperLimitRegister;
+use crate::reg_temp_generic::ReadableTempRegister;
+use crate::snapshot::{RawRegisters, RegisterSnapshot};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::InputPin;
 
-pub mod error;
-mod prelude;
 pub mod address;
+pub mod alert;
+pub mod alert_pin;
+pub mod array;
+#[cfg(feature = "async")]
+pub mod asynch;
+pub mod celsius;
+mod device;
+pub mod error;
+#[cfg(any(feature = "sim", test))]
+pub mod fault;
+pub mod interface;
+pub mod mux;
+pub mod prelude;
+pub mod record;
 pub mod reg;
 pub mod reg_conf;
 pub mod reg_device_id;
@@ -30,30 +62,64 @@ pub mod reg_temp_alert_crit;
 pub mod reg_temp_alert_lower;
 pub mod reg_temp_alert_upper;
 pub mod reg_temp_generic;
+#[cfg(any(feature = "sim", test))]
+pub mod sim;
+pub mod snapshot;
 
 /// MCP9808 Driver
 pub struct MCP9808<I2C> {
-    addr: u8,
     i2c: I2C,
+    state: DeviceState,
+}
+
+impl<I2C> MCP9808<I2C> {
+    /// driver for a device the bus-independent `state` is known of
+    pub(crate) fn with_state(i2c: I2C, state: DeviceState) -> Self {
+        MCP9808 { i2c, state }
+    }
 }
 
 impl<I2C> MCP9808<I2C>
 where
-    I2C: I2c<SevenBitAddress>,
-    I2C::Error: Into<Error<I2C::Error>>,
+    I2C: RegisterInterface,
 {
-    /// Creates a new driver from an I2C peripheral.
+    /// Creates a new driver from an I2C peripheral or another `RegisterInterface`.
     pub fn new(i2c: I2C) -> Self {
-        MCP9808 {
-            addr: SlaveAddress::Default.into(),
-            i2c,
-        }
+        Self::with_state(i2c, DeviceState::new(SlaveAddress::Default))
+    }
+
+    /// Creates a new driver and verifies that an MCP9808 answers at `addr`.
+    pub fn probe(i2c: I2C, addr: SlaveAddress) -> Result<Self, Error<I2C::Error>> {
+        let mut dev = Self::new(i2c);
+        dev.set_address(addr);
+        dev.verify_identity()?;
+        Ok(dev)
+    }
+
+    /// Checks manufacturer and device ID and records the device revision.
+    pub fn verify_identity(&mut self) -> Result<(), Error<I2C::Error>> {
+        let manuf = self.read_manufacturer_id()?;
+        device::check_manufacturer(&manuf)?;
+        let device = self.read_device_id()?;
+        self.state.identify(&device)
+    }
+
+    /// Device revision, `None` until the identity was verified.
+    pub fn device_revision(&self) -> Option<u8> {
+        self.state.revision
     }
 
     /// Change i2c address
     pub fn set_address(&mut self, addr: SlaveAddress) -> u8 {
-        self.addr = addr.into();
-        self.addr
+        self.state.set_address(addr)
+    }
+
+    /// Reads the register the device already points to without writing the pointer again.
+    ///
+    /// Only enable this if nothing else accesses the device, such as another driver instance or
+    /// bus master, the driver does not notice the pointer changing under it.
+    pub fn set_pointer_cache(&mut self, enabled: bool) {
+        self.state.set_pointer_cache(enabled);
     }
 
     /// release resources
@@ -61,53 +127,739 @@ where
         self.i2c
     }
 
-    fn read_register<T>(&mut self, mut reg: T) -> Result<T, Error<I2C::Error>>
-    where
-        T: prelude::Read,
-        I2C: I2c<SevenBitAddress>,
-        I2C::Error: Into<Error<I2C::Error>>,
-    {
-        reg.read_from_device(&mut self.i2c, self.addr)?;
+    /// Pairs the driver with the input pin wired to the ALERT output.
+    ///
+    /// Reads the configured alert polarity from the device.
+    pub fn with_alert_pin<P: InputPin>(
+        mut self,
+        pin: P,
+    ) -> Result<WithAlertPin<I2C, P>, Error<I2C::Error>> {
+        let polarity = self.read_configuration()?.get_alert_polarity();
+        Ok(WithAlertPin::new(self, pin, polarity))
+    }
+
+    fn read_register<T: prelude::Read>(&mut self, mut reg: T) -> Result<T, Error<I2C::Error>> {
+        let ptr = reg.register().get_ptr();
+        let res = if self.state.is_pointing_to(ptr) {
+            reg.read_current_from_device(&mut self.i2c, self.state.addr)
+        } else {
+            reg.read_from_device(&mut self.i2c, self.state.addr)
+        };
+        self.state.read_done(ptr, res.is_ok());
+        res?;
         Ok(reg)
     }
 
+    /// Fails with `Error::Locked` if a lock bit would make the device ignore writing `reg`.
     pub fn write_register<R: prelude::Write>(&mut self, reg: R) -> Result<(), Error<I2C::Error>> {
-        reg.write_to_device(&mut self.i2c, self.addr)?;
-        Ok(())
+        if self.state.needs_lock_check(reg.register()) {
+            let current = self.read_configuration()?;
+            LockState::check_write(&current, reg.register()).map_err(Error::Locked)?;
+        }
+        let res = reg.write_to_device(&mut self.i2c, self.state.addr);
+        self.state.write_done(reg.register(), res.is_ok());
+        res
+    }
+
+    /// Also updates the cached lock state.
+    pub fn read_configuration(&mut self) -> Result<ConfigurationRegister, Error<I2C::Error>> {
+        let reg = self.read_register(reg_conf::new())?;
+        self.state.config_read(&reg);
+        Ok(reg)
+    }
+
+    /// Reads the current lock bits from the device.
+    pub fn lock_state(&mut self) -> Result<LockState, Error<I2C::Error>> {
+        let reg = self.read_configuration()?;
+        Ok(LockState::from_register(&reg))
+    }
+
+    /// Locks tUPPER and tLOWER, and most configuration bits, until the next power-on reset.
+    pub fn lock_window(&mut self) -> Result<(), Error<I2C::Error>> {
+        let mut reg = self.read_configuration()?;
+        reg.set_window_lock(WindowLock::Locked);
+        self.write_register(reg)
+    }
+
+    /// Locks tCRIT, and most configuration bits, until the next power-on reset.
+    pub fn lock_critical(&mut self) -> Result<(), Error<I2C::Error>> {
+        let mut reg = self.read_configuration()?;
+        reg.set_critical_lock(CriticalLock::Locked);
+        self.write_register(reg)
+    }
+
+    /// Writes the whole configuration at once and verifies it by reading it back.
+    ///
+    /// Fails with `Error::ConfigNotApplied` listing the fields the device did not accept.
+    pub fn apply_config(&mut self, config: &ConfigBuilder) -> Result<(), Error<I2C::Error>> {
+        self.write_register(config.build())?;
+        let actual = self.read_configuration()?;
+        config
+            .check_applied(&actual)
+            .map_err(Error::ConfigNotApplied)
+    }
+
+    /// Writes tUPPER, returns the value stored after rounding down to 0.25 °C.
+    pub fn set_alert_upper(&mut self, temp: Celsius) -> Result<Celsius, Error<I2C::Error>> {
+        self.set_limit(Limit::Upper, temp)
+    }
+
+    /// Writes tLOWER, returns the value stored after rounding down to 0.25 °C.
+    pub fn set_alert_lower(&mut self, temp: Celsius) -> Result<Celsius, Error<I2C::Error>> {
+        self.set_limit(Limit::Lower, temp)
+    }
+
+    /// Writes tCRIT, returns the value stored after rounding down to 0.25 °C.
+    pub fn set_alert_critical(&mut self, temp: Celsius) -> Result<Celsius, Error<I2C::Error>> {
+        self.set_limit(Limit::Critical, temp)
+    }
+
+    fn set_limit(&mut self, limit: Limit, temp: Celsius) -> Result<Celsius, Error<I2C::Error>> {
+        let reg = limit.encode(temp)?;
+        self.write_register(reg)?;
+        Ok(reg.get_celsius(ResolutionVal::Deg_0_25C))
     }
 
-    pub fn read_configuration(&mut self) -> Result<impl Configuration, Error<I2C::Error>> {
-        self.read_register(reg_conf::new())
+    fn read_limit(&mut self, limit: Limit) -> Result<Celsius, Error<I2C::Error>> {
+        let reg = self.read_register(limit.register())?;
+        Ok(reg.get_celsius(ResolutionVal::Deg_0_25C))
     }
 
-    pub fn read_device_id(&mut self) -> Result<impl DeviceId, Error<I2C::Error>> {
+    /// Programs all alert limits and the hysteresis, and enables the alert output.
+    ///
+    /// The limits are validated by `AlertWindow::new()` before anything is written, and verified
+    /// by reading them back afterwards. Fails with `Error::Locked` without writing anything if
+    /// either lock bit is set.
+    pub fn configure_alert_window(
+        &mut self,
+        lower: Celsius,
+        upper: Celsius,
+        critical: Celsius,
+        hysteresis: Hysteresis,
+    ) -> Result<AlertWindow, Error<I2C::Error>> {
+        let window = AlertWindow::new(lower, upper, critical, hysteresis)?;
+
+        let mut conf = self.read_configuration()?;
+        LockState::from_register(&conf)
+            .check_limits()
+            .map_err(Error::Locked)?;
+        for limit in Limit::ALL {
+            self.set_limit(limit, window.limit(limit))?;
+        }
+        window.configure(&mut conf);
+        self.write_register(conf)?;
+
+        for limit in Limit::ALL {
+            if self.read_limit(limit)? != window.limit(limit) {
+                return Err(Error::LimitNotApplied(limit));
+            }
+        }
+        Ok(window)
+    }
+
+    /// Reads the alert flags and clears the interrupt, if the alert output is in interrupt mode.
+    ///
+    /// The interrupt is not cleared while TA ≥ tCRIT, the output then stays asserted until the
+    /// temperature drops below tCRIT - hysteresis.
+    pub fn service_alert(&mut self) -> Result<AlertEvent, Error<I2C::Error>> {
+        let temp = self.read_temperature()?;
+        let mut event = AlertEvent::from_register(&temp, self.state.resolution);
+
+        let conf = self.read_configuration()?;
+        if let Some(clear) = alert::interrupt_clear(conf, &event) {
+            self.write_register(clear)?;
+            event.cleared = true;
+        }
+        Ok(event)
+    }
+
+    /// Turns the alert output into a change detector, asserted once the temperature moved by more
+    /// than `delta`.
+    ///
+    /// Enables interrupt-mode alerts and centres tLOWER/tUPPER on the current temperature, see
+    /// `recenter_wake_on_change()`. tCRIT is left as it is, it should stay above the temperatures
+    /// the window can move to.
+    pub fn enable_wake_on_change<D: DelayNs>(
+        &mut self,
+        delta: Celsius,
+        delay: &mut D,
+    ) -> Result<ChangeWindow, Error<I2C::Error>> {
+        let mut conf = self.read_configuration()?;
+        ChangeWindow::configure(&mut conf);
+        self.write_register(conf)?;
+        self.recenter_wake_on_change(delta, delay)
+    }
+
+    /// Re-centres the window of `enable_wake_on_change()` on the current temperature and clears
+    /// the interrupt, call it on each alert.
+    ///
+    /// The device only compares against the new limits at its next conversion, so the interrupt is
+    /// cleared after waiting the typical conversion time of the cached resolution. Clearing it
+    /// earlier would let that conversion raise it again.
+    pub fn recenter_wake_on_change<D: DelayNs>(
+        &mut self,
+        delta: Celsius,
+        delay: &mut D,
+    ) -> Result<ChangeWindow, Error<I2C::Error>> {
+        let temp = self.read_celsius()?;
+        let window = ChangeWindow::around(temp, delta)?;
+        self.set_limit(Limit::Lower, window.lower)?;
+        self.set_limit(Limit::Upper, window.upper)?;
+
+        // moving the limits across the temperature can raise another interrupt
+        delay.delay_ms(self.state.resolution.conversion_time_ms());
+        let mut conf = self.read_configuration()?;
+        conf.set_interrupt_clear(InterruptClear::ClearInterruptOutput);
+        self.write_register(conf)?;
+        Ok(window)
+    }
+
+    pub fn read_device_id(&mut self) -> Result<DeviceIdRegister, Error<I2C::Error>> {
         self.read_register(reg_device_id::new())
     }
 
-    pub fn read_manufacturer_id(&mut self) -> Result<impl ManufacturerId, Error<I2C::Error>> {
+    pub fn read_manufacturer_id(&mut self) -> Result<ManufacturerIdRegister, Error<I2C::Error>> {
         self.read_register(reg_manuf_id::new())
     }
 
-    pub fn read_resolution(&mut self) -> Result<impl Resolution, Error<I2C::Error>> {
-        self.read_register(reg_res::new())
+    /// Also updates the cached resolution, see `resolution()`.
+    pub fn read_resolution(&mut self) -> Result<ResolutionRegister, Error<I2C::Error>> {
+        let reg = self.read_register(reg_res::new())?;
+        self.state.resolution_read(&reg);
+        Ok(reg)
+    }
+
+    /// Writes the resolution register and updates the cached resolution.
+    pub fn set_resolution(&mut self, res: ResolutionVal) -> Result<(), Error<I2C::Error>> {
+        let mut reg = reg_res::new();
+        reg.set_resolution(res);
+        self.write_register(reg)
+    }
+
+    /// Reads the resolution from the device and caches it.
+    pub fn sync_resolution(&mut self) -> Result<ResolutionVal, Error<I2C::Error>> {
+        let reg = self.read_register(reg_res::new())?;
+        self.state.resolution = reg.get_resolution()?;
+        Ok(self.state.resolution)
+    }
+
+    /// Resolution last read from or written to the device, the power-up default until then.
+    pub fn resolution(&self) -> ResolutionVal {
+        self.state.resolution
     }
 
     /// Read temperature register. Its double-buffered so no wait required.
-    pub fn read_temperature(&mut self) -> Result<impl Temperature, Error<I2C::Error>> {
+    pub fn read_temperature(&mut self) -> Result<TemperatureRegister, Error<I2C::Error>> {
         self.read_register(reg_temp::new())
     }
 
-    pub fn read_alert_critical(
+    /// Reads the temperature, decoded with the cached resolution.
+    pub fn read_celsius(&mut self) -> Result<Celsius, Error<I2C::Error>> {
+        let reg = self.read_temperature()?;
+        Ok(reg.get_celsius(self.state.resolution))
+    }
+
+    /// Wakes the device from shutdown for a single conversion and shuts it down again.
+    ///
+    /// Waits the typical conversion time of the cached resolution. Fails with `Error::Locked`
+    /// before waking the device if a lock bit would prevent it from entering shutdown again.
+    pub fn measure_once<D: DelayNs>(
         &mut self,
-    ) -> Result<impl CriticalTemperatureAlert, Error<I2C::Error>> {
+        delay: &mut D,
+    ) -> Result<Celsius, Error<I2C::Error>> {
+        let mut conf = self.read_configuration()?;
+        LockState::check_one_shot(&conf).map_err(Error::Locked)?;
+
+        conf.set_shutdown_mode(ShutdownMode::Continuous);
+        self.write_register(conf)?;
+        delay.delay_ms(self.state.resolution.conversion_time_ms());
+        let temp = self.read_celsius();
+
+        conf.set_shutdown_mode(ShutdownMode::Shutdown);
+        self.write_register(conf)?;
+        temp
+    }
+
+    /// Like `read_celsius()`, rounded to the nearest m°C for 0.0625 °C resolution.
+    pub fn read_millicelsius(&mut self) -> Result<i32, Error<I2C::Error>> {
+        Ok(self.read_celsius()?.to_millicelsius())
+    }
+
+    pub fn read_alert_critical(&mut self) -> Result<CriticalLimitRegister, Error<I2C::Error>> {
         self.read_register(reg_temp_alert_crit::new())
     }
 
-    pub fn read_alert_lower(&mut self) -> Result<impl LowerTemperatureAlert, Error<I2C::Error>> {
+    pub fn read_alert_lower(&mut self) -> Result<LowerLimitRegister, Error<I2C::Error>> {
         self.read_register(reg_temp_alert_lower::new())
     }
 
-    pub fn read_alert_upper(&mut self) -> Result<impl UpperTemperatureAlert, Error<I2C::Error>> {
+    pub fn read_alert_upper(&mut self) -> Result<UpperLimitRegister, Error<I2C::Error>> {
         self.read_register(reg_temp_alert_upper::new())
     }
+
+    /// Reads all registers at once, in a single transaction on an I2C bus.
+    ///
+    /// Also updates the cached resolution and lock state.
+    pub fn read_all(&mut self) -> Result<RegisterSnapshot, Error<I2C::Error>> {
+        let mut raw = RawRegisters::default();
+        let res = self.i2c.read_registers(self.state.addr, &mut raw.batch());
+        self.state.read_all_done(&raw, res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reg_conf::{AlertControl, AlertPolarity, ConfigDiff, Hysteresis, Lock};
+    use crate::reg_temp_generic::WritableTempRegister;
+    use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    const ADDR: u8 = 0b1_1000;
+
+    #[test]
+    fn cached_resolution() {
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0b0101], vec![0b00000001, 0b10010111]),
+            Transaction::write(ADDR, vec![0b1000, 0b01]),
+            Transaction::write_read(ADDR, vec![0b0101], vec![0b00000001, 0b10010111]),
+            Transaction::write_read(ADDR, vec![0b1000], vec![0b10]),
+            Transaction::write_read(ADDR, vec![0b0101], vec![0b00000001, 0b10010111]),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+        assert_eq!(dev.resolution(), ResolutionVal::Deg_0_0625C);
+
+        // 25.4375 °C at power-up resolution
+        assert_eq!(dev.read_celsius().unwrap(), Celsius::from_sixteenths(407));
+
+        dev.set_resolution(ResolutionVal::Deg_0_25C).unwrap();
+        assert_eq!(dev.resolution(), ResolutionVal::Deg_0_25C);
+        assert_eq!(dev.read_millicelsius().unwrap(), 25250);
+
+        assert_eq!(dev.sync_resolution().unwrap(), ResolutionVal::Deg_0_125C);
+        assert_eq!(dev.read_millicelsius().unwrap(), 25375);
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn pointer_cache() {
+        let temp = vec![0x01, 0x94];
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0b0101], temp.clone()),
+            Transaction::read(ADDR, temp.clone()),
+            Transaction::write_read(ADDR, vec![0b0001], vec![0x00, 0x00]),
+            Transaction::write_read(ADDR, vec![0b0101], temp.clone()),
+            // errors and writes leave the pointer unknown
+            Transaction::read(ADDR, temp.clone()).with_error(embedded_hal::i2c::ErrorKind::Other),
+            Transaction::write_read(ADDR, vec![0b0101], temp.clone()),
+            Transaction::write(ADDR, vec![0b0010, 0x01, 0xe0]),
+            Transaction::write_read(ADDR, vec![0b0010], vec![0x01, 0xe0]),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+        dev.set_pointer_cache(true);
+
+        assert_eq!(dev.read_millicelsius().unwrap(), 25_250);
+        assert_eq!(dev.read_millicelsius().unwrap(), 25_250);
+        dev.read_configuration().unwrap();
+        assert_eq!(dev.read_millicelsius().unwrap(), 25_250);
+
+        assert!(matches!(dev.read_millicelsius(), Err(Error::I2c(_))));
+        assert_eq!(dev.read_millicelsius().unwrap(), 25_250);
+        dev.set_alert_upper(Celsius::from_degrees(30)).unwrap();
+        dev.read_alert_upper().unwrap();
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn read_all() {
+        let registers = [
+            (0b0001, vec![0x00, 0x08]),
+            (0b0010, vec![0x01, 0xe0]),
+            (0b0011, vec![0x00, 0x00]),
+            (0b0100, vec![0x02, 0x80]),
+            (0b0101, vec![0x41, 0x94]),
+            (0b0110, vec![0x00, 0x54]),
+            (0b0111, vec![0x04, 0x00]),
+            (0b1000, vec![0b10]),
+        ];
+        let mut expectations = vec![Transaction::transaction_start(ADDR)];
+        for (ptr, data) in registers {
+            expectations.push(Transaction::write(ADDR, vec![ptr]));
+            expectations.push(Transaction::read(ADDR, data));
+        }
+        expectations.push(Transaction::transaction_end(ADDR));
+        // the resolution register is read last
+        expectations.push(Transaction::read(ADDR, vec![0b10]));
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+        dev.set_pointer_cache(true);
+
+        let snapshot = dev.read_all().unwrap();
+        assert_eq!(snapshot.temperature, Celsius::from_millicelsius(25_250));
+        assert!(snapshot.above_upper);
+        assert_eq!(snapshot.critical, Celsius::from_degrees(40));
+        assert_eq!(snapshot.manufacturer_id, 0x0054);
+        assert_eq!(dev.resolution(), ResolutionVal::Deg_0_125C);
+        dev.read_resolution().unwrap();
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn probe() {
+        let addr = SlaveAddress::Alternative {
+            a2: false,
+            a1: true,
+            a0: true,
+        };
+        let expectations = [
+            Transaction::write_read(0b1_1011, vec![0b0110], vec![0x00, 0x54]),
+            Transaction::write_read(0b1_1011, vec![0b0111], vec![0x04, 0x01]),
+        ];
+        let dev = MCP9808::probe(Mock::new(&expectations), addr).unwrap();
+        assert_eq!(dev.device_revision(), Some(0x01));
+        dev.free().done();
+    }
+
+    #[test]
+    fn probe_wrong_device() {
+        let expectations = [Transaction::write_read(
+            ADDR,
+            vec![0b0110],
+            vec![0x00, 0x55],
+        )];
+        let mut i2c = Mock::new(&expectations);
+        let err = MCP9808::probe(i2c.clone(), SlaveAddress::Default).err();
+        assert!(matches!(err, Some(Error::WrongManufacturer(0x0055))));
+        i2c.done();
+
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0b0110], vec![0x00, 0x54]),
+            Transaction::write_read(ADDR, vec![0b0111], vec![0x75, 0x02]),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+        let err = dev.verify_identity().err();
+        assert!(matches!(
+            err,
+            Some(Error::WrongDevice {
+                id: 0x75,
+                rev: 0x02
+            })
+        ));
+        assert_eq!(dev.device_revision(), None);
+        dev.free().done();
+    }
+
+    #[test]
+    fn apply_config() {
+        let config = ConfigBuilder::new()
+            .alert_control(AlertControl::Enabled)
+            .hysteresis(Hysteresis::Deg_1_5C);
+        let expectations = [
+            // lock state is unknown at first
+            Transaction::write_read(ADDR, vec![0b0001], vec![0b0000_0000, 0b0000_0000]),
+            Transaction::write(ADDR, vec![0b0001, 0b0000_0010, 0b0000_1000]),
+            Transaction::write_read(ADDR, vec![0b0001], vec![0b0000_0010, 0b0001_1000]),
+            Transaction::write(ADDR, vec![0b0001, 0b0000_0010, 0b0000_1000]),
+            // device kept the alert output disabled
+            Transaction::write_read(ADDR, vec![0b0001], vec![0b0000_0010, 0b0000_0000]),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+
+        // alert status bit is not part of the configuration
+        dev.apply_config(&config).unwrap();
+
+        let err = dev.apply_config(&config).err();
+        let expected = ConfigDiff {
+            alert_control: true,
+            ..ConfigDiff::default()
+        };
+        assert!(matches!(err, Some(Error::ConfigNotApplied(diff)) if diff == expected));
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn locked_writes() {
+        let locked_conf = vec![0b0000_0000, 0b0100_0000];
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0b0001], vec![0b0000_0000, 0b0000_0000]),
+            Transaction::write(ADDR, vec![0b0001, 0b0000_0000, 0b0100_0000]),
+            // tUPPER write refused without touching the bus beyond the lock check
+            Transaction::write_read(ADDR, vec![0b0001], locked_conf.clone()),
+            // tCRIT is not affected by the window lock
+            Transaction::write_read(ADDR, vec![0b0001], locked_conf.clone()),
+            Transaction::write(ADDR, vec![0b0100, 0b0000_0101, 0b1010_0000]),
+            Transaction::write_read(ADDR, vec![0b0001], locked_conf.clone()),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+
+        dev.lock_window().unwrap();
+
+        let mut upper = reg_temp_alert_upper::new();
+        upper.set_milli_celsius(80_000).unwrap();
+        assert!(matches!(
+            dev.write_register(upper),
+            Err(Error::Locked(Lock::Window))
+        ));
+
+        let mut crit = reg_temp_alert_crit::new();
+        crit.set_milli_celsius(90_000).unwrap();
+        dev.write_register(crit).unwrap();
+
+        let locks = dev.lock_state().unwrap();
+        assert!(locks.is_window_locked() && !locks.is_critical_locked());
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn measure_once() {
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0b1000], vec![0b01]),
+            Transaction::write_read(ADDR, vec![0b0001], vec![0b0000_0001, 0b0000_0000]),
+            Transaction::write(ADDR, vec![0b0001, 0b0000_0000, 0b0000_0000]),
+            Transaction::write_read(ADDR, vec![0b0101], vec![0b1111_1111, 0b1001_1100]),
+            Transaction::write(ADDR, vec![0b0001, 0b0000_0001, 0b0000_0000]),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_ms(65)]);
+
+        dev.sync_resolution().unwrap();
+        let temp = dev.measure_once(&mut delay).unwrap();
+        assert_eq!(temp, Celsius::from_millicelsius(-6_250));
+
+        dev.free().done();
+        delay.done();
+    }
+
+    #[test]
+    fn measure_once_locked() {
+        let expectations = [Transaction::write_read(
+            ADDR,
+            vec![0b0001],
+            vec![0b0000_0001, 0b1000_0000],
+        )];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+        let mut delay = CheckedDelay::new(&[]);
+
+        let err = dev.measure_once(&mut delay).err();
+        assert!(matches!(err, Some(Error::Locked(Lock::Critical))));
+
+        dev.free().done();
+        delay.done();
+    }
+
+    #[test]
+    fn configure_alert_window() {
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0b0001], vec![0x00, 0x00]),
+            Transaction::write(ADDR, vec![0b0011, 0x1f, 0x60]),
+            Transaction::write(ADDR, vec![0b0010, 0x01, 0xe4]),
+            Transaction::write(ADDR, vec![0b0100, 0x02, 0xd0]),
+            Transaction::write(ADDR, vec![0b0001, 0x02, 0x08]),
+            Transaction::write_read(ADDR, vec![0b0011], vec![0x1f, 0x60]),
+            Transaction::write_read(ADDR, vec![0b0010], vec![0x01, 0xe4]),
+            Transaction::write_read(ADDR, vec![0b0100], vec![0x02, 0xd0]),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+
+        let window = dev
+            .configure_alert_window(
+                Celsius::from_degrees(-10),
+                Celsius::from_millicelsius(30_250),
+                Celsius::from_degrees(45),
+                Hysteresis::Deg_1_5C,
+            )
+            .unwrap();
+        assert_eq!(window.upper(), Celsius::from_millicelsius(30_250));
+
+        // invalid windows never reach the bus
+        let err = dev
+            .configure_alert_window(
+                Celsius::from_degrees(45),
+                Celsius::from_degrees(30),
+                Celsius::from_degrees(50),
+                Hysteresis::Deg_0_0C,
+            )
+            .err();
+        assert!(matches!(err, Some(Error::InvalidAlertWindow)));
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn configure_alert_window_locked() {
+        // only tCRIT is locked, the window limits would still be accepted
+        let expectations = [Transaction::write_read(
+            ADDR,
+            vec![0b0001],
+            vec![0x00, 0x80],
+        )];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+
+        let err = dev
+            .configure_alert_window(
+                Celsius::ZERO,
+                Celsius::from_degrees(30),
+                Celsius::from_degrees(45),
+                Hysteresis::Deg_0_0C,
+            )
+            .err();
+        assert!(matches!(err, Some(Error::Locked(Lock::Critical))));
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn configure_alert_window_not_applied() {
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0b0001], vec![0x00, 0x00]),
+            Transaction::write(ADDR, vec![0b0011, 0x00, 0x00]),
+            Transaction::write(ADDR, vec![0b0010, 0x01, 0x40]),
+            Transaction::write(ADDR, vec![0b0100, 0x01, 0x40]),
+            Transaction::write(ADDR, vec![0b0001, 0x00, 0x08]),
+            Transaction::write_read(ADDR, vec![0b0011], vec![0x00, 0x00]),
+            Transaction::write_read(ADDR, vec![0b0010], vec![0x00, 0x00]),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+
+        let err = dev
+            .configure_alert_window(
+                Celsius::ZERO,
+                Celsius::from_degrees(20),
+                Celsius::from_degrees(20),
+                Hysteresis::Deg_0_0C,
+            )
+            .err();
+        assert!(matches!(err, Some(Error::LimitNotApplied(Limit::Upper))));
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn set_alert_limits() {
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0b0001], vec![0x00, 0x00]),
+            Transaction::write(ADDR, vec![0b0010, 0x05, 0xa4]),
+            Transaction::write(ADDR, vec![0b0011, 0x1f, 0x5c]),
+            Transaction::write(ADDR, vec![0b0100, 0x07, 0xd0]),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+
+        let stored = dev
+            .set_alert_upper(Celsius::from_millicelsius(90_300))
+            .unwrap();
+        assert_eq!(stored, Celsius::from_millicelsius(90_250));
+        let stored = dev
+            .set_alert_lower(Celsius::from_millicelsius(-10_100))
+            .unwrap();
+        assert_eq!(stored, Celsius::from_millicelsius(-10_250));
+        let stored = dev.set_alert_critical(Celsius::from_degrees(125)).unwrap();
+        assert_eq!(stored, Celsius::from_degrees(125));
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn service_alert() {
+        let expectations = [
+            // 31 °C, TA > tUPPER, interrupt asserted
+            Transaction::write_read(ADDR, vec![0b0101], vec![0x41, 0xf0]),
+            Transaction::write_read(ADDR, vec![0b0001], vec![0x02, 0x19]),
+            Transaction::write(ADDR, vec![0b0001, 0x02, 0x39]),
+            // 46 °C, TA ≥ tCRIT can not be cleared
+            Transaction::write_read(ADDR, vec![0b0101], vec![0xc2, 0xe0]),
+            Transaction::write_read(ADDR, vec![0b0001], vec![0x02, 0x19]),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+
+        let event = dev.service_alert().unwrap();
+        assert!(event.upper && !event.critical && !event.lower);
+        assert_eq!(event.temperature, Celsius::from_degrees(31));
+        assert!(event.cleared);
+
+        let event = dev.service_alert().unwrap();
+        assert!(event.critical && event.upper);
+        assert_eq!(event.temperature, Celsius::from_degrees(46));
+        assert!(!event.cleared);
+
+        dev.free().done();
+    }
+
+    #[test]
+    fn alert_pin() {
+        use embedded_hal_mock::eh1::digital::{
+            Mock as PinMock, State, Transaction as PinTransaction,
+        };
+
+        let expectations = [
+            // active-low, interrupt mode
+            Transaction::write_read(ADDR, vec![0b0001], vec![0x00, 0x09]),
+            // 9.5 °C, TA < tLOWER
+            Transaction::write_read(ADDR, vec![0b0101], vec![0x20, 0x98]),
+            Transaction::write_read(ADDR, vec![0b0001], vec![0x00, 0x19]),
+            Transaction::write(ADDR, vec![0b0001, 0x00, 0x39]),
+        ];
+        let pin = PinMock::new(&[
+            PinTransaction::get(State::High),
+            PinTransaction::get(State::High),
+            PinTransaction::get(State::Low),
+        ]);
+        let mut delay = CheckedDelay::new(&[DelayTransaction::delay_ms(10)]);
+        let mut dev = MCP9808::new(Mock::new(&expectations))
+            .with_alert_pin(pin)
+            .unwrap();
+        assert_eq!(dev.polarity(), AlertPolarity::ActiveLow);
+
+        assert!(!dev.is_alert_asserted().unwrap());
+        let event = dev.wait_for_alert(&mut delay, 10).unwrap();
+        assert!(event.lower && event.cleared);
+        assert_eq!(event.temperature, Celsius::from_millicelsius(9_500));
+
+        let (dev, mut pin) = dev.free();
+        dev.free().done();
+        pin.done();
+        delay.done();
+    }
+
+    #[test]
+    fn wake_on_change() {
+        let expectations = [
+            Transaction::write_read(ADDR, vec![0b0001], vec![0x00, 0x00]),
+            Transaction::write(ADDR, vec![0b0001, 0x00, 0x09]),
+            // 21.5 °C
+            Transaction::write_read(ADDR, vec![0b0101], vec![0x01, 0x58]),
+            Transaction::write(ADDR, vec![0b0011, 0x01, 0x48]),
+            Transaction::write(ADDR, vec![0b0010, 0x01, 0x68]),
+            Transaction::write_read(ADDR, vec![0b0001], vec![0x00, 0x09]),
+            Transaction::write(ADDR, vec![0b0001, 0x00, 0x29]),
+            // 22.5625 °C, TA > tUPPER
+            Transaction::write_read(ADDR, vec![0b0101], vec![0x41, 0x69]),
+            Transaction::write(ADDR, vec![0b0011, 0x01, 0x58]),
+            Transaction::write(ADDR, vec![0b0010, 0x01, 0x7c]),
+            Transaction::write_read(ADDR, vec![0b0001], vec![0x00, 0x19]),
+            Transaction::write(ADDR, vec![0b0001, 0x00, 0x39]),
+        ];
+        let mut dev = MCP9808::new(Mock::new(&expectations));
+        // one conversion at 0.0625 °C before each clear
+        let mut delay = CheckedDelay::new(&[
+            DelayTransaction::delay_ms(250),
+            DelayTransaction::delay_ms(250),
+        ]);
+        let delta = Celsius::from_degrees(1);
+
+        let window = dev.enable_wake_on_change(delta, &mut delay).unwrap();
+        assert_eq!(window.lower, Celsius::from_millicelsius(20_500));
+        assert_eq!(window.upper, Celsius::from_millicelsius(22_500));
+
+        let window = dev.recenter_wake_on_change(delta, &mut delay).unwrap();
+        assert_eq!(window.temperature, Celsius::from_millicelsius(22_562));
+        assert_eq!(window.lower, Celsius::from_millicelsius(21_500));
+        assert_eq!(window.upper, Celsius::from_millicelsius(23_750));
+
+        dev.free().done();
+        delay.done();
+    }
 }
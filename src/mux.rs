@@ -0,0 +1,300 @@
+//! Sensors behind a TCA9548A style I2C multiplexer
+
+use crate::address::SlaveAddress;
+use crate::device::DeviceState;
+use crate::error::Error;
+use crate::interface::RegisterInterface;
+use crate::reg_res::ResolutionVal;
+use crate::MCP9808;
+
+/// TCA9548A address with A2, A1, A0 low
+pub const DEFAULT_MUX_ADDRESS: u8 = 0x70;
+const CHANNELS: u8 = 8;
+
+/// Sensor behind a multiplexer: the channel it is wired to and its address on that channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MuxedAddress {
+    pub channel: u8,
+    pub addr: SlaveAddress,
+}
+
+/// Sensor behind a `Mux`, keeping the driver state between accesses through `Mux::with_sensor()`
+pub struct MuxedSensor {
+    id: MuxedAddress,
+    /// driver state without the bus
+    state: DeviceState,
+}
+
+impl MuxedSensor {
+    pub fn new(id: MuxedAddress) -> Self {
+        MuxedSensor {
+            id,
+            state: DeviceState::new(id.addr),
+        }
+    }
+
+    pub fn id(&self) -> MuxedAddress {
+        self.id
+    }
+
+    /// resolution last read from or written to the device
+    pub fn resolution(&self) -> ResolutionVal {
+        self.state.resolution
+    }
+}
+
+/// Channel-select I2C multiplexer owning the upstream bus.
+///
+/// One channel is enabled at a time, the selected channel is cached so the control register is
+/// only written when switching. The control register is a single byte without a pointer, it is
+/// written as a register write of the channel mask with no data, so any `RegisterInterface` can
+/// carry the mux.
+///
+/// # Example
+///
+/// ```
+/// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+/// use mcp9808::address::SlaveAddress;
+/// use mcp9808::mux::{Mux, MuxedAddress, MuxedSensor, DEFAULT_MUX_ADDRESS};
+///
+/// # let i2c = Mock::new(&[
+/// #     Transaction::write(0x70, vec![0b1000]),
+/// #     Transaction::write_read(0x18, vec![5], vec![0x01, 0x94]),
+/// # ]);
+/// let mut mux = Mux::new(i2c, DEFAULT_MUX_ADDRESS);
+/// let mut sensor = MuxedSensor::new(MuxedAddress { channel: 3, addr: SlaveAddress::Default });
+/// let temp = mux.with_sensor(&mut sensor, |dev| dev.read_celsius()).unwrap();
+/// # mux.free().done();
+/// ```
+pub struct Mux<I2C> {
+    i2c: I2C,
+    addr: u8,
+    /// channel last selected, `None` if unknown
+    selected: Option<u8>,
+}
+
+impl<I2C> Mux<I2C>
+where
+    I2C: RegisterInterface,
+{
+    pub fn new(i2c: I2C, addr: u8) -> Self {
+        Mux {
+            i2c,
+            addr,
+            selected: None,
+        }
+    }
+
+    /// channel last selected, `None` until then or after a failed switch
+    pub fn selected(&self) -> Option<u8> {
+        self.selected
+    }
+
+    /// Enables `channel` and disables all others, unless it is already selected.
+    pub fn select(&mut self, channel: u8) -> Result<(), Error<I2C::Error>> {
+        if channel >= CHANNELS {
+            return Err(Error::InvalidChannel(channel));
+        }
+        self.switch(channel).map_err(Error::I2c)
+    }
+
+    /// `select()` for a channel known to be valid
+    fn switch(&mut self, channel: u8) -> Result<(), I2C::Error> {
+        if self.selected == Some(channel) {
+            return Ok(());
+        }
+        self.selected = None;
+        self.i2c.write_register(self.addr, 1 << channel, &[])?;
+        self.selected = Some(channel);
+        Ok(())
+    }
+
+    /// Bus of `channel`, which is selected before each access.
+    pub fn channel(&mut self, channel: u8) -> Result<Channel<'_, I2C>, Error<I2C::Error>> {
+        if channel >= CHANNELS {
+            return Err(Error::InvalidChannel(channel));
+        }
+        Ok(Channel { mux: self, channel })
+    }
+
+    /// Runs `f` with the driver of `sensor` on its channel.
+    ///
+    /// State the driver caches, such as the resolution, is kept in `sensor` for the next access.
+    pub fn with_sensor<T, F>(
+        &mut self,
+        sensor: &mut MuxedSensor,
+        f: F,
+    ) -> Result<T, Error<I2C::Error>>
+    where
+        F: FnOnce(&mut MCP9808<Channel<'_, I2C>>) -> Result<T, Error<I2C::Error>>,
+    {
+        let channel = self.channel(sensor.id.channel)?;
+        let mut dev = MCP9808::with_state(channel, sensor.state);
+        let res = f(&mut dev);
+        sensor.state = dev.state;
+        res
+    }
+
+    /// release resources
+    pub fn free(self) -> I2C {
+        self.i2c
+    }
+}
+
+/// One downstream bus of a `Mux`, a `RegisterInterface` for the sensors wired to it
+pub struct Channel<'a, I2C> {
+    mux: &'a mut Mux<I2C>,
+    channel: u8,
+}
+
+impl<I2C> Channel<'_, I2C>
+where
+    I2C: RegisterInterface,
+{
+    /// the upstream bus, with this channel selected
+    fn bus(&mut self) -> Result<&mut I2C, I2C::Error> {
+        // the channel number was checked by `Mux::channel()`
+        self.mux.switch(self.channel)?;
+        Ok(&mut self.mux.i2c)
+    }
+}
+
+impl<I2C> RegisterInterface for Channel<'_, I2C>
+where
+    I2C: RegisterInterface,
+{
+    type Error = I2C::Error;
+
+    fn read_register(&mut self, addr: u8, ptr: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus()?.read_register(addr, ptr, buf)
+    }
+
+    fn read_current_register(&mut self, addr: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus()?.read_current_register(addr, buf)
+    }
+
+    fn write_register(&mut self, addr: u8, ptr: u8, data: &[u8]) -> Result<(), Self::Error> {
+        self.bus()?.write_register(addr, ptr, data)
+    }
+
+    fn read_registers(
+        &mut self,
+        addr: u8,
+        regs: &mut [(u8, &mut [u8])],
+    ) -> Result<(), Self::Error> {
+        self.bus()?.read_registers(addr, regs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::celsius::Celsius;
+    use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+
+    /// mux with one sensor per entry, answering temperature and resolution reads only
+    struct SimMux {
+        enabled: u8,
+        /// (channel, address, temperature register)
+        sensors: Vec<(u8, u8, [u8; 2])>,
+        switches: usize,
+    }
+
+    impl ErrorType for SimMux {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for SimMux {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+            if address == DEFAULT_MUX_ADDRESS {
+                if let [Operation::Write([mask])] = operations {
+                    self.enabled = *mask;
+                    self.switches += 1;
+                    return Ok(());
+                }
+                return Err(ErrorKind::Other);
+            }
+
+            let enabled = self.enabled;
+            let sensor = self
+                .sensors
+                .iter()
+                .find(|(ch, addr, _)| enabled & 1 << ch != 0 && *addr == address)
+                .ok_or(nack)?;
+            match operations {
+                [Operation::Write([0b0101]), Operation::Read(buf)] => {
+                    buf.copy_from_slice(&sensor.2);
+                    Ok(())
+                }
+                // 0.5 °C resolution
+                [Operation::Write([0b1000]), Operation::Read([res])] => {
+                    *res = 0b00;
+                    Ok(())
+                }
+                _ => Err(ErrorKind::Other),
+            }
+        }
+    }
+
+    fn sensor(channel: u8) -> MuxedSensor {
+        MuxedSensor::new(MuxedAddress {
+            channel,
+            addr: SlaveAddress::Default,
+        })
+    }
+
+    #[test]
+    fn switch_channels() {
+        let sim = SimMux {
+            enabled: 0,
+            sensors: vec![(0, 0x18, [0x01, 0x90]), (5, 0x18, [0x00, 0x50])],
+            switches: 0,
+        };
+        let mut mux = Mux::new(sim, DEFAULT_MUX_ADDRESS);
+
+        // same address on two channels
+        let (mut five, mut zero) = (sensor(5), sensor(0));
+        let temp = mux.with_sensor(&mut five, |dev| dev.read_celsius());
+        assert_eq!(temp.unwrap(), Celsius::from_degrees(5));
+        assert_eq!(mux.selected(), Some(5));
+        let temp = mux.with_sensor(&mut zero, |dev| dev.read_celsius());
+        assert_eq!(temp.unwrap(), Celsius::from_degrees(25));
+        // no switch needed
+        mux.with_sensor(&mut zero, |dev| dev.read_celsius())
+            .unwrap();
+
+        let err = mux.with_sensor(&mut sensor(3), |dev| dev.read_celsius());
+        assert!(matches!(err, Err(Error::I2c(ErrorKind::NoAcknowledge(_)))));
+        let err = mux.with_sensor(&mut sensor(8), |dev| dev.read_celsius());
+        assert!(matches!(err, Err(Error::InvalidChannel(8))));
+
+        let sim = mux.free();
+        assert_eq!(sim.switches, 3);
+        assert_eq!(sim.enabled, 1 << 3);
+    }
+
+    #[test]
+    fn sensor_state() {
+        let sim = SimMux {
+            enabled: 0,
+            // 25.4375 °C
+            sensors: vec![(2, 0x18, [0x01, 0x97])],
+            switches: 0,
+        };
+        let mut mux = Mux::new(sim, DEFAULT_MUX_ADDRESS);
+        let mut sensor = sensor(2);
+
+        mux.with_sensor(&mut sensor, |dev| dev.sync_resolution())
+            .unwrap();
+        assert_eq!(sensor.resolution(), ResolutionVal::Deg_0_5C);
+        // decoded with the resolution cached by the previous access
+        let temp = mux.with_sensor(&mut sensor, |dev| dev.read_celsius());
+        assert_eq!(temp.unwrap(), Celsius::from_degrees(25));
+        assert_eq!(mux.free().switches, 1);
+    }
+}
@@ -1,52 +1,82 @@
+//! Register access traits
+
 use crate::error::Error;
+use crate::interface::RegisterInterface;
 use crate::reg::Register;
 use core::fmt::Debug;
-use embedded_hal::i2c::{I2c, SevenBitAddress};
 
-/// trait for a register that can be read from an i2c device
+/// trait for a register that can be read from the device
 pub trait Read: Debug + Copy + Clone {
-    fn read_from_device<I2C>(&mut self, i2c: &mut I2C, addr: u8) -> Result<(), Error<I2C::Error>>
+    /// the raw register backing this type
+    fn register(&self) -> &Register;
+
+    /// mutable access to the raw register backing this type
+    fn register_mut(&mut self) -> &mut Register;
+
+    fn read_from_device<R>(&mut self, bus: &mut R, addr: u8) -> Result<(), Error<R::Error>>
     where
-        I2C: I2c<SevenBitAddress>,
-        I2C::Error: Into<Error<I2C::Error>>;
-}
+        R: RegisterInterface,
+    {
+        let reg = self.register_mut();
+        let ptr = reg.get_ptr();
+        bus.read_register(addr, ptr, reg.buf_mut())
+            .map_err(Error::I2c)
+    }
 
-impl Read for Register {
-    fn read_from_device<I2C>(&mut self, i2c: &mut I2C, addr: u8) -> Result<(), Error<I2C::Error>>
+    /// same as `read_from_device()`, for a device already pointing to this register
+    fn read_current_from_device<R>(&mut self, bus: &mut R, addr: u8) -> Result<(), Error<R::Error>>
     where
-        I2C: I2c<SevenBitAddress>,
-        I2C::Error: Into<Error<I2C::Error>>,
+        R: RegisterInterface,
     {
-        let mut buf = [0u8; 2];
-        i2c.write_read(
-            addr,
-            &[self.get_ptr()],
-            &mut buf[0..self.get_len() as usize],
-        )?;
-        self.set_buf(buf);
-        Ok(())
+        bus.read_current_register(addr, self.register_mut().buf_mut())
+            .map_err(Error::I2c)
     }
 }
 
-/// trait for a register that can be written to an i2c device
-pub trait Write: Read {
-    fn write_to_device<I2C>(&self, i2c: &mut I2C, addr: u8) -> Result<(), Error<I2C::Error>>
-    where
-        I2C: I2c<SevenBitAddress>,
-        I2C::Error: Into<Error<I2C::Error>>;
+impl Read for Register {
+    fn register(&self) -> &Register {
+        self
+    }
+
+    fn register_mut(&mut self) -> &mut Register {
+        self
+    }
 }
 
-impl Write for Register {
-    fn write_to_device<I2C>(&self, i2c: &mut I2C, addr: u8) -> Result<(), Error<I2C::Error>>
+/// trait for a register that can be written to the device
+pub trait Write: Read {
+    fn write_to_device<R>(&self, bus: &mut R, addr: u8) -> Result<(), Error<R::Error>>
     where
-        I2C: I2c<SevenBitAddress>,
-        I2C::Error: Into<Error<I2C::Error>>,
+        R: RegisterInterface,
     {
-        // reg ptr + 1 or 2 bytes
-        let mut buf = [self.get_ptr(); 3];
-        for (i, item) in self.get_buf().iter().enumerate() {
-            buf[i + 1] = *item;
-        }
-        Ok(i2c.write(addr, &buf[0..self.get_len() as usize])?)
+        let reg = self.register();
+        bus.write_register(addr, reg.get_ptr(), reg.get_buf())
+            .map_err(Error::I2c)
+    }
+}
+
+impl Write for Register {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    #[test]
+    fn write_to_device() {
+        let expectations = [
+            Transaction::write(0x18, vec![0b0010, 0x05, 0xa0]),
+            Transaction::write(0x18, vec![0b1000, 0b11]),
+        ];
+        let mut i2c = Mock::new(&expectations);
+
+        let mut reg = Register::new(0b0010, 2).unwrap();
+        reg.set_buf([0x05, 0xa0]);
+        reg.write_to_device(&mut i2c, 0x18).unwrap();
+
+        let mut reg = Register::new(0b1000, 1).unwrap();
+        reg.set_msb(0b11);
+        reg.write_to_device(&mut i2c, 0x18).unwrap();
+        i2c.done();
     }
 }
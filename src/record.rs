@@ -0,0 +1,440 @@
+//! Recording and replaying bus traffic
+//!
+//! `Recorder` logs every call to a byte buffer, `Replay` plays such a log back to the driver.
+//! `Replay` is test tooling that panics on a mismatch, it needs the `sim` feature.
+//!
+//! Log format, one entry per call:
+//! `address, status, op count` followed by `kind, length, bytes` per operation, where status is
+//! 0 for success or an error code, and kind is 0 for a write and 1 for a read.
+
+use embedded_hal::i2c::{
+    Error as _, ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation, SevenBitAddress,
+};
+
+const OP_WRITE: u8 = 0;
+const OP_READ: u8 = 1;
+
+/// One operation of a recorded call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordedOp<'a> {
+    /// bytes written
+    Write(&'a [u8]),
+    /// bytes read
+    Read(&'a [u8]),
+}
+
+/// One recorded call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry<'a> {
+    pub address: u8,
+    pub result: Result<(), ErrorKind>,
+    count: u8,
+    ops: &'a [u8],
+}
+
+impl<'a> Entry<'a> {
+    pub fn ops(&self) -> impl Iterator<Item = RecordedOp<'a>> + 'a {
+        let mut rest = self.ops;
+        (0..self.count).map_while(move |_| {
+            let (op, tail) = decode_op(rest)?;
+            rest = tail;
+            Some(op)
+        })
+    }
+
+    /// register pointer, the first byte written
+    pub fn pointer(&self) -> Option<u8> {
+        match self.ops().next() {
+            Some(RecordedOp::Write([ptr, ..])) => Some(*ptr),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes the entries of a log.
+pub fn entries(log: &[u8]) -> impl Iterator<Item = Entry<'_>> {
+    let mut rest = log;
+    core::iter::from_fn(move || {
+        let (entry, tail) = decode_entry(rest)?;
+        rest = tail;
+        Some(entry)
+    })
+}
+
+fn decode_op(buf: &[u8]) -> Option<(RecordedOp<'_>, &[u8])> {
+    let (&kind, buf) = buf.split_first()?;
+    let (&len, buf) = buf.split_first()?;
+    let bytes = buf.get(..len as usize)?;
+    let rest = buf.get(len as usize..)?;
+    let op = match kind {
+        OP_WRITE => RecordedOp::Write(bytes),
+        OP_READ => RecordedOp::Read(bytes),
+        _ => return None,
+    };
+    Some((op, rest))
+}
+
+fn decode_entry(buf: &[u8]) -> Option<(Entry<'_>, &[u8])> {
+    let [address, status, count, ..] = *buf else {
+        return None;
+    };
+    let ops = buf.get(3..)?;
+    let mut rest = ops;
+    for _ in 0..count {
+        rest = decode_op(rest)?.1;
+    }
+    let len = ops.len() - rest.len();
+    let entry = Entry {
+        address,
+        result: decode_status(status)?,
+        count,
+        ops: ops.get(..len)?,
+    };
+    Some((entry, rest))
+}
+
+fn encode_status(result: Result<(), ErrorKind>) -> u8 {
+    match result {
+        Ok(()) => 0,
+        Err(ErrorKind::Bus) => 1,
+        Err(ErrorKind::ArbitrationLoss) => 2,
+        Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)) => 3,
+        Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data)) => 4,
+        Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown)) => 5,
+        Err(ErrorKind::Overrun) => 6,
+        Err(_) => 7,
+    }
+}
+
+fn decode_status(status: u8) -> Option<Result<(), ErrorKind>> {
+    Some(match status {
+        0 => Ok(()),
+        1 => Err(ErrorKind::Bus),
+        2 => Err(ErrorKind::ArbitrationLoss),
+        3 => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+        4 => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data)),
+        5 => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown)),
+        6 => Err(ErrorKind::Overrun),
+        7 => Err(ErrorKind::Other),
+        _ => return None,
+    })
+}
+
+/// Transparent I2C wrapper logging every call into a buffer.
+///
+/// Recording stops at the first entry that does not fit, see `is_truncated()`.
+///
+/// # Example
+///
+/// ```
+/// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+/// use mcp9808::record::{entries, Recorder};
+/// use mcp9808::MCP9808;
+///
+/// # let i2c = Mock::new(&[Transaction::write_read(0x18, vec![5], vec![0x01, 0x94])]);
+/// let mut log = [0u8; 256];
+/// let mut dev = MCP9808::new(Recorder::new(i2c, &mut log));
+/// dev.read_celsius().unwrap();
+///
+/// let recorder = dev.free();
+/// let entry = entries(recorder.log()).next().unwrap();
+/// assert_eq!(entry.pointer(), Some(0b0101));
+/// # recorder.free().done();
+/// ```
+#[derive(Debug)]
+pub struct Recorder<'a, I2C> {
+    inner: I2C,
+    buf: &'a mut [u8],
+    len: usize,
+    truncated: bool,
+}
+
+impl<'a, I2C> Recorder<'a, I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    pub fn new(inner: I2C, buf: &'a mut [u8]) -> Self {
+        Recorder {
+            inner,
+            buf,
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    /// the log recorded so far
+    pub fn log(&self) -> &[u8] {
+        self.buf.get(..self.len).unwrap_or_default()
+    }
+
+    /// whether calls were dropped because the buffer is full
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// release resources
+    pub fn free(self) -> I2C {
+        self.inner
+    }
+
+    fn record<'b, T>(&mut self, address: u8, result: &Result<(), I2C::Error>, ops: T)
+    where
+        T: Iterator<Item = RecordedOp<'b>> + Clone,
+    {
+        if self.truncated {
+            return;
+        }
+        let status = encode_status(result.as_ref().map(|_| ()).map_err(|e| e.kind()));
+        let count = ops.clone().count();
+        let size = 3 + ops.clone().map(|op| 2 + op_bytes(op).len()).sum::<usize>();
+        let fits = count <= u8::MAX as usize
+            && ops.clone().all(|op| op_bytes(op).len() <= u8::MAX as usize);
+        let Some(entry) = self.buf.get_mut(self.len..self.len + size).filter(|_| fits) else {
+            self.truncated = true;
+            return;
+        };
+
+        let mut out = entry.iter_mut();
+        let mut put = |byte: u8| {
+            if let Some(dst) = out.next() {
+                *dst = byte;
+            }
+        };
+        put(address);
+        put(status);
+        put(count as u8);
+        for op in ops {
+            let kind = match op {
+                RecordedOp::Write(_) => OP_WRITE,
+                RecordedOp::Read(_) => OP_READ,
+            };
+            put(kind);
+            put(op_bytes(op).len() as u8);
+            op_bytes(op).iter().for_each(|b| put(*b));
+        }
+        self.len += size;
+    }
+}
+
+fn op_bytes(op: RecordedOp<'_>) -> &[u8] {
+    match op {
+        RecordedOp::Write(bytes) | RecordedOp::Read(bytes) => bytes,
+    }
+}
+
+impl<I2C: ErrorType> ErrorType for Recorder<'_, I2C> {
+    type Error = I2C::Error;
+}
+
+impl<I2C> I2c<SevenBitAddress> for Recorder<'_, I2C>
+where
+    I2C: I2c<SevenBitAddress>,
+{
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        let res = self.inner.read(address, read);
+        self.record(address, &res, [RecordedOp::Read(read)].into_iter());
+        res
+    }
+
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        let res = self.inner.write(address, write);
+        self.record(address, &res, [RecordedOp::Write(write)].into_iter());
+        res
+    }
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let res = self.inner.write_read(address, write, read);
+        let ops = [RecordedOp::Write(write), RecordedOp::Read(read)];
+        self.record(address, &res, ops.into_iter());
+        res
+    }
+
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let res = self.inner.transaction(address, operations);
+        let ops = operations.iter().map(|op| match op {
+            Operation::Write(bytes) => RecordedOp::Write(bytes),
+            Operation::Read(bytes) => RecordedOp::Read(bytes),
+        });
+        self.record(address, &res, ops);
+        res
+    }
+}
+
+/// I2C bus playing back a log of `Recorder`, panics as soon as the driver diverges from it.
+///
+/// Only available with the `sim` feature.
+#[cfg(any(feature = "sim", test))]
+#[derive(Debug, Clone)]
+pub struct Replay<'a> {
+    log: &'a [u8],
+    /// index of the next entry
+    index: usize,
+}
+
+#[cfg(any(feature = "sim", test))]
+#[allow(clippy::panic)]
+impl<'a> Replay<'a> {
+    pub fn new(log: &'a [u8]) -> Self {
+        Replay { log, index: 0 }
+    }
+
+    /// Panics unless the whole log was played back.
+    pub fn done(&self) {
+        if !self.log.is_empty() {
+            panic!(
+                "replay: {} of {} calls made",
+                self.index,
+                self.index + entries(self.log).count()
+            );
+        }
+    }
+}
+
+#[cfg(any(feature = "sim", test))]
+impl ErrorType for Replay<'_> {
+    type Error = ErrorKind;
+}
+
+#[cfg(any(feature = "sim", test))]
+#[allow(clippy::panic)]
+impl I2c<SevenBitAddress> for Replay<'_> {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let index = self.index;
+        let Some((entry, rest)) = decode_entry(self.log) else {
+            panic!(
+                "replay: call {} at {:#04x} beyond the end of the log",
+                index, address
+            );
+        };
+        if entry.address != address {
+            panic!(
+                "replay: call {} addresses {:#04x}, recorded {:#04x}",
+                index, address, entry.address
+            );
+        }
+        if entry.ops().count() != operations.len() {
+            panic!(
+                "replay: call {} has {} operations, recorded {}",
+                index,
+                operations.len(),
+                entry.ops().count()
+            );
+        }
+        for (op, recorded) in operations.iter_mut().zip(entry.ops()) {
+            match (op, recorded) {
+                (Operation::Write(bytes), RecordedOp::Write(expected)) if *bytes == expected => {}
+                (Operation::Read(buf), RecordedOp::Read(data)) if buf.len() == data.len() => {
+                    buf.copy_from_slice(data);
+                }
+                (op, recorded) => panic!(
+                    "replay: call {} diverges, {:?} instead of recorded {:?}",
+                    index, op, recorded
+                ),
+            }
+        }
+        self.log = rest;
+        self.index += 1;
+        entry.result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::SlaveAddress;
+    use crate::celsius::Celsius;
+    use crate::error::Error;
+    use crate::fault::{Fault, FaultyI2c};
+    use crate::reg_res::ResolutionVal;
+    use crate::sim::SimulatedMcp9808;
+    use crate::MCP9808;
+
+    type Temps<E> = [Result<Celsius, Error<E>>; 2];
+
+    /// probe, set the resolution and read twice
+    fn session<I2C: I2c>(i2c: I2C) -> (MCP9808<I2C>, Temps<I2C::Error>) {
+        let mut dev = MCP9808::probe(i2c, SlaveAddress::Default).unwrap();
+        dev.set_resolution(ResolutionVal::Deg_0_25C).unwrap();
+        let temps = [dev.read_celsius(), dev.read_celsius()];
+        (dev, temps)
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let mut sim = SimulatedMcp9808::new(SlaveAddress::Default);
+        sim.set_temperature(Celsius::from_sixteenths(-83));
+        let schedule = [(4, Fault::NackAddress)];
+        let mut log = [0u8; 64];
+
+        let (dev, recorded) = session(Recorder::new(FaultyI2c::new(sim, &schedule), &mut log));
+        let recorder = dev.free();
+        assert!(!recorder.is_truncated());
+        let log = recorder.log();
+
+        let calls: Vec<_> = entries(log).collect();
+        assert_eq!(calls.len(), 5);
+        assert_eq!(calls[2].pointer(), Some(0b1000));
+        assert_eq!(
+            calls[3].ops().collect::<Vec<_>>(),
+            [
+                RecordedOp::Write(&[0b0101]),
+                RecordedOp::Read(&[0x3f, 0xad])
+            ]
+        );
+        assert_eq!(
+            calls[4].result,
+            Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))
+        );
+
+        let (dev, replayed) = session(Replay::new(log));
+        dev.free().done();
+        assert_eq!(recorded[0].as_ref().unwrap(), replayed[0].as_ref().unwrap());
+        assert_eq!(
+            replayed[0].as_ref().unwrap(),
+            &Celsius::from_sixteenths(-84)
+        );
+        assert!(matches!(
+            replayed[1],
+            Err(Error::I2c(ErrorKind::NoAcknowledge(_)))
+        ));
+    }
+
+    #[test]
+    fn truncated() {
+        // room for one temperature read
+        let mut log = [0u8; 16];
+        let sim = SimulatedMcp9808::new(SlaveAddress::Default);
+        let mut dev = MCP9808::new(Recorder::new(sim, &mut log));
+        dev.read_celsius().unwrap();
+        dev.read_celsius().unwrap();
+        let recorder = dev.free();
+        assert!(recorder.is_truncated());
+        assert_eq!(entries(recorder.log()).count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "diverges")]
+    fn divergence() {
+        let mut log = [0u8; 16];
+        let sim = SimulatedMcp9808::new(SlaveAddress::Default);
+        let mut dev = MCP9808::new(Recorder::new(sim, &mut log));
+        dev.read_celsius().unwrap();
+        let log = dev.free().log().to_vec();
+
+        let mut dev = MCP9808::new(Replay::new(&log));
+        let _ = dev.read_configuration();
+    }
+}
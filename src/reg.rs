@@ -1,6 +1,7 @@
+use crate::error::ValueError;
 use bit_field::BitField;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Register {
     /// points to a specific register in the sensor
     ptr: u8,
@@ -11,21 +12,35 @@ pub struct Register {
 }
 
 impl Register {
-    pub fn new(ptr: u8, len: u8) -> Self {
+    /// Creates a register with pointer `ptr` of `len` bytes.
+    ///
+    /// Fails for reserved pointer values and lengths other than 1 or 2.
+    pub fn new(ptr: u8, len: u8) -> Result<Self, ValueError> {
         if ptr == 0 || ptr > 0b1000u8 {
-            panic!("invalid pointer value: reserved")
+            return Err(ValueError::InvalidPointer(ptr));
         }
 
-        if len > 2 {
-            panic!("length > 2")
+        if len == 0 || len > 2 {
+            return Err(ValueError::RegisterSizeMismatch(len));
         }
 
+        Ok(Self::new_unchecked(ptr, len))
+    }
+
+    /// only for the fixed pointer and size constants of the register modules
+    pub(crate) const fn new_unchecked(ptr: u8, len: u8) -> Self {
         let buf = [0u8, 0];
         Register { ptr, buf, len }
     }
 
     pub fn get_buf(&self) -> &[u8] {
-        &self.buf[0..self.len as usize]
+        self.buf.get(0..self.len as usize).unwrap_or(&self.buf)
+    }
+
+    /// bus buffer, sized to the register
+    pub(crate) fn buf_mut(&mut self) -> &mut [u8] {
+        let len = self.len as usize;
+        self.buf.get_mut(0..len).unwrap_or(&mut [])
     }
 
     pub fn set_buf(&mut self, val: [u8; 2]) {
@@ -61,41 +76,48 @@ impl Register {
         self.buf[0] = val;
     }
 
+    /// bits beyond the register size read as `false`
     pub fn get_bit(&self, offset: usize) -> bool {
-        if self.len == 1 {
-            return self.get_msb().get_bit(offset);
-        }
-
-        if offset > 7 {
-            self.get_msb().get_bit(offset - 8)
-        } else {
-            self.get_lsb().unwrap().get_bit(offset)
-        }
+        offset < self.len as usize * 8
+            && self.as_u16().checked_shr(offset as u32).unwrap_or(0) & 1 == 1
     }
 
     /// datasheet numbers bits from lsb-0, we store them as msb
-    pub fn set_bit(&mut self, offset: usize, val: bool) {
-        if offset + 1 > self.len as usize * 8 {
-            panic!("out of bounds access")
+    pub fn set_bit(&mut self, offset: usize, val: bool) -> Result<(), ValueError> {
+        if offset >= self.len as usize * 8 {
+            return Err(ValueError::BitOutOfBounds(offset));
         }
+        self.update_bit(offset, val);
+        Ok(())
+    }
 
-        if self.len == 1 {
-            self.buf[0].set_bit(offset, val);
+    /// infallible `set_bit` for the fixed bit offsets of the register modules,
+    /// out of bounds offsets are ignored
+    pub(crate) fn update_bit(&mut self, offset: usize, val: bool) {
+        if offset >= self.len as usize * 8 {
+            return;
         }
+        let mut raw = self.as_u16();
+        raw.set_bit(offset, val);
+        self.set_u16(raw);
+    }
 
-        if offset > 7 {
-            self.buf[0].set_bit(offset - 8, val);
-        } else {
-            self.buf[1].set_bit(offset, val);
+    pub fn as_u16(&self) -> u16 {
+        match self.get_lsb() {
+            Some(lo) => u16::from_be_bytes([self.get_msb(), lo]),
+            None => self.get_msb() as u16,
         }
     }
 
-    pub fn as_u16(&self) -> u16 {
-        let (lo, hi) = (self.get_lsb(), self.get_msb());
-        if lo.is_none() {
-            return self.get_msb() as u16;
+    /// counterpart to `as_u16`, 1 byte registers only keep the lower byte
+    pub fn set_u16(&mut self, val: u16) {
+        let [hi, lo] = val.to_be_bytes();
+        if self.len < 2 {
+            self.set_msb(lo);
+        } else {
+            self.set_msb(hi);
+            self.set_lsb(lo);
         }
-        ((hi as u16) << 8) + (lo.unwrap() as u16)
     }
 }
 
@@ -106,16 +128,52 @@ mod tests {
 
     #[test]
     fn bitfield_manipulation() {
-        let mut reg: Register = Register::new(1, 2);
+        let mut reg: Register = Register::new(1, 2).unwrap();
 
         assert_eq!(reg.as_u16(), 0);
 
         assert_eq!(reg.get_bit(15), false);
-        reg.set_bit(15, true);
+        reg.set_bit(15, true).unwrap();
         assert_eq!(reg.get_bit(15), true);
 
         assert_eq!(reg.get_bit(0), false);
-        reg.set_bit(0, true);
+        reg.set_bit(0, true).unwrap();
         assert_eq!(reg.get_bit(0), true);
     }
+
+    #[test]
+    fn single_byte_register() {
+        let mut reg: Register = Register::new(8, 1).unwrap();
+
+        reg.set_bit(1, true).unwrap();
+        assert_eq!(reg.get_bit(1), true);
+        assert_eq!(reg.get_buf(), &[0b10]);
+
+        assert!(matches!(
+            reg.set_bit(8, true),
+            Err(ValueError::BitOutOfBounds(8))
+        ));
+        assert_eq!(reg.get_bit(8), false);
+        assert_eq!(reg.get_bit(64), false);
+    }
+
+    #[test]
+    fn invalid_construction() {
+        assert!(matches!(
+            Register::new(0, 2),
+            Err(ValueError::InvalidPointer(0))
+        ));
+        assert!(matches!(
+            Register::new(9, 2),
+            Err(ValueError::InvalidPointer(9))
+        ));
+        assert!(matches!(
+            Register::new(1, 3),
+            Err(ValueError::RegisterSizeMismatch(3))
+        ));
+        assert!(matches!(
+            Register::new(1, 0),
+            Err(ValueError::RegisterSizeMismatch(0))
+        ));
+    }
 }
@@ -1,10 +1,13 @@
+use crate::celsius::Celsius;
+use crate::prelude::{Read, Write};
+use crate::reg::Register;
+use crate::{reg_temp_alert_crit, reg_temp_alert_lower, reg_temp_alert_upper};
 use bit_field::BitField;
-use prelude::Write;
-use reg::Register;
 
 /// Alert Output Mode bit
 /// This bit cannot be altered when either of the Lock bits are set (bit 6 and bit 7).
 /// This bit can be programmed in Shutdown mode, but the Alert output will not assert or deassert.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AlertMode {
     /// power-up default
     Comparator = 0,
@@ -14,15 +17,35 @@ pub enum AlertMode {
 /// Alert Output Polarity bit
 /// This bit cannot be altered when either of the Lock bits are set (bit 6 and bit 7).
 /// This bit can be programmed in Shutdown mode, but the Alert output will not assert or deassert.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AlertPolarity {
     /// power-up default; pull-up resistor required
     ActiveLow = 0,
     ActiveHigh = 1,
 }
 
+impl AlertPolarity {
+    /// whether the ALERT output is asserted, given the level of the pin
+    pub fn is_asserted(self, high: bool) -> bool {
+        match self {
+            AlertPolarity::ActiveLow => !high,
+            AlertPolarity::ActiveHigh => high,
+        }
+    }
+
+    /// level of the pin while the ALERT output is `asserted` or not
+    pub fn pin_level(self, asserted: bool) -> bool {
+        match self {
+            AlertPolarity::ActiveLow => !asserted,
+            AlertPolarity::ActiveHigh => asserted,
+        }
+    }
+}
+
 /// Alert Output Select bit
 /// When the Alarm Window Lock bit is set, this bit cannot be altered until unlocked (bit 6).
 /// This bit can be programmed in Shutdown mode, but the Alert output will not assert or deassert.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AlertSelect {
     /// Alert output for TUPPER, TLOWER and TCRIT (power-up default)
     All = 0,
@@ -32,6 +55,7 @@ pub enum AlertSelect {
 /// Alert Output Control bit
 /// This bit can not be altered when either of the Lock bits are set (bit 6 and bit 7).
 /// This bit can be programmed in Shutdown mode, but the Alert output will not assert or deassert.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AlertControl {
     /// power-up default
     Disabled = 0,
@@ -43,6 +67,7 @@ pub enum AlertControl {
 /// However, if the Alert output is configured as Interrupt mode, and if the host controller clears
 /// to ‘0’, the interrupt, using bit 5 while the device is in Shutdown mode,
 /// then this bit will also be cleared ‘0’.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AlertStatus {
     /// Alert output is not asserted by the device (power-up default)
     NotAsserted = 0,
@@ -53,6 +78,7 @@ pub enum AlertStatus {
 /// Interrupt Clear bit
 /// This bit can not be set to ‘1’ in Shutdown mode, but it can be cleared after the device enters
 /// Shutdown mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum InterruptClear {
     /// No effect (power-up default)
     NoEffect = 0,
@@ -62,6 +88,7 @@ pub enum InterruptClear {
 
 /// tUPPER and tLOWER Window Lock bit
 /// When enabled, this bit remains set to ‘1’ or locked until cleared by a Power-on Reset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum WindowLock {
     /// tUPPER and tLOWER registers can be written (power-up default)
     Unlocked = 0,
@@ -71,6 +98,7 @@ pub enum WindowLock {
 
 /// When  enabled,  this  bit  remains  set  to  ‘1’  or  locked  until  cleared  by  an  internal
 /// Reset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CriticalLock {
     /// tCRIT register can be written (power-up default)
     Unlocked = 0,
@@ -81,7 +109,7 @@ pub enum CriticalLock {
 /// In shutdown, all power-consuming activities are disabled, though all registers can be written to or read.
 /// This bit cannot be set to ‘1’ when either of the Lock bits is set (bit 6 and bit 7).
 /// However, it can be cleared to ‘0’ for continuous conversion while locked.
-
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ShutdownMode {
     /// Continuous conversion (power-up default)
     Continuous = 0,
@@ -94,7 +122,7 @@ pub enum ShutdownMode {
 /// This bit can not be altered when either of the Lock bits are set (bit 6 and bit 7).
 /// This bit can be programmed in Shutdown mode.
 #[allow(non_camel_case_types)]
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Hysteresis {
     Deg_0_0C = 0b00,
@@ -103,6 +131,17 @@ pub enum Hysteresis {
     Deg_6_0C = 0b11,
 }
 
+impl Hysteresis {
+    pub fn to_celsius(self) -> Celsius {
+        match self {
+            Hysteresis::Deg_0_0C => Celsius::ZERO,
+            Hysteresis::Deg_1_5C => Celsius::from_sixteenths(24),
+            Hysteresis::Deg_3_0C => Celsius::from_degrees(3),
+            Hysteresis::Deg_6_0C => Celsius::from_degrees(6),
+        }
+    }
+}
+
 const REGISTER_PTR: u8 = 0b0001;
 const REGISTER_SIZE: u8 = 2;
 
@@ -138,129 +177,437 @@ pub trait Configuration: Write {
     fn get_hysteresis(&self) -> Hysteresis;
 }
 
-pub fn new() -> Register {
-    Register::new(REGISTER_PTR, REGISTER_SIZE)
+/// Sensor configuration register.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConfigurationRegister(Register);
+
+pub fn new() -> ConfigurationRegister {
+    ConfigurationRegister(Register::new_unchecked(REGISTER_PTR, REGISTER_SIZE))
+}
+
+/// lock bits held by `reg`, if it is the configuration register
+pub(crate) fn locks_of(reg: &Register) -> Option<LockState> {
+    if reg.get_ptr() != REGISTER_PTR {
+        return None;
+    }
+    Some(LockState::from_register(&ConfigurationRegister(*reg)))
 }
 
-/// Sensor configuration register.
-impl Configuration for Register {
+impl Read for ConfigurationRegister {
+    fn register(&self) -> &Register {
+        &self.0
+    }
+
+    fn register_mut(&mut self) -> &mut Register {
+        &mut self.0
+    }
+}
+
+impl Write for ConfigurationRegister {}
+
+impl Configuration for ConfigurationRegister {
     fn get_alert_mode(&self) -> AlertMode {
-        if self.get_bit(0) {
+        if self.0.get_bit(0) {
             return AlertMode::Interrupt;
         }
         AlertMode::Comparator
     }
 
     fn set_alert_mode(&mut self, mode: AlertMode) {
-        self.set_bit(0, bool(mode as isize));
+        self.0.update_bit(0, bool(mode as isize));
     }
 
     fn get_alert_polarity(&self) -> AlertPolarity {
-        if self.get_bit(1) {
+        if self.0.get_bit(1) {
             return AlertPolarity::ActiveHigh;
         }
         AlertPolarity::ActiveLow
     }
 
     fn set_alert_polarity(&mut self, mode: AlertPolarity) {
-        self.set_bit(1, bool(mode as isize));
+        self.0.update_bit(1, bool(mode as isize));
     }
 
     fn get_alert_select(&self) -> AlertSelect {
-        if self.get_bit(2) {
+        if self.0.get_bit(2) {
             return AlertSelect::TCritOnly;
         }
         AlertSelect::All
     }
 
     fn set_alert_select(&mut self, mode: AlertSelect) {
-        self.set_bit(2, bool(mode as isize));
+        self.0.update_bit(2, bool(mode as isize));
     }
 
     fn get_alert_control(&self) -> AlertControl {
-        if self.get_bit(3) {
+        if self.0.get_bit(3) {
             return AlertControl::Enabled;
         }
         AlertControl::Disabled
     }
 
     fn set_alert_control(&mut self, mode: AlertControl) {
-        self.set_bit(3, bool(mode as isize));
+        self.0.update_bit(3, bool(mode as isize));
     }
 
     fn get_alert_status(&self) -> AlertStatus {
-        if self.get_bit(4) {
+        if self.0.get_bit(4) {
             return AlertStatus::Asserted;
         }
         AlertStatus::NotAsserted
     }
 
     fn set_alert_status(&mut self, mode: AlertStatus) {
-        self.set_bit(4, bool(mode as isize));
+        self.0.update_bit(4, bool(mode as isize));
     }
 
     fn get_interrupt_clear(&self) -> InterruptClear {
-        if self.get_bit(5) {
+        if self.0.get_bit(5) {
             return InterruptClear::ClearInterruptOutput;
         }
         InterruptClear::NoEffect
     }
 
     fn set_interrupt_clear(&mut self, mode: InterruptClear) {
-        self.set_bit(5, bool(mode as isize));
+        self.0.update_bit(5, bool(mode as isize));
     }
 
     fn get_window_lock(&self) -> WindowLock {
-        if self.get_bit(6) {
+        if self.0.get_bit(6) {
             return WindowLock::Locked;
         }
         WindowLock::Unlocked
     }
 
     fn set_window_lock(&mut self, mode: WindowLock) {
-        self.set_bit(6, bool(mode as isize));
+        self.0.update_bit(6, bool(mode as isize));
     }
 
     fn get_critical_lock(&self) -> CriticalLock {
-        if self.get_bit(7) {
+        if self.0.get_bit(7) {
             return CriticalLock::Locked;
         }
         CriticalLock::Unlocked
     }
 
     fn set_critical_lock(&mut self, mode: CriticalLock) {
-        self.set_bit(7, bool(mode as isize));
+        self.0.update_bit(7, bool(mode as isize));
     }
 
     fn get_shutdown_mode(&self) -> ShutdownMode {
-        if self.get_bit(8) {
+        if self.0.get_bit(8) {
             return ShutdownMode::Shutdown;
         }
         ShutdownMode::Continuous
     }
 
     fn set_shutdown_mode(&mut self, mode: ShutdownMode) {
-        self.set_bit(8, bool(mode as isize));
+        self.0.update_bit(8, bool(mode as isize));
     }
 
-    #[allow(unused_must_use, clippy::unnecessary_operation)] // TODO: address these allows
     fn set_hysteresis(&mut self, mode: Hysteresis) {
-        &self.set_bit(9, (mode as i64).get_bit(0));
-        &self.set_bit(10, (mode as i64).get_bit(1));
+        self.0.update_bit(9, (mode as u8).get_bit(0));
+        self.0.update_bit(10, (mode as u8).get_bit(1));
     }
 
     fn get_hysteresis(&self) -> Hysteresis {
-        let val: u8 = &self.get_msb() >> 1 & 0b11u8;
-        match val {
-            val if val == Hysteresis::Deg_0_0C as u8 => Hysteresis::Deg_0_0C,
-            val if val == Hysteresis::Deg_1_5C as u8 => Hysteresis::Deg_1_5C,
-            val if val == Hysteresis::Deg_3_0C as u8 => Hysteresis::Deg_3_0C,
-            val if val == Hysteresis::Deg_6_0C as u8 => Hysteresis::Deg_6_0C,
-            _ => panic!("invalid value"),
+        // masked to two bits, every value is a valid setting
+        match self.0.get_msb() >> 1 & 0b11u8 {
+            0b00 => Hysteresis::Deg_0_0C,
+            0b01 => Hysteresis::Deg_1_5C,
+            0b10 => Hysteresis::Deg_3_0C,
+            _ => Hysteresis::Deg_6_0C,
         }
     }
 }
 
+/// Complete sensor configuration, applied with a single register write.
+///
+/// Starts out with the power-up defaults. Status and interrupt clear bits are not part of it,
+/// as they are not settings.
+///
+/// # Example
+///
+/// ```
+/// use mcp9808::reg_conf::{AlertControl, AlertMode, ConfigBuilder, Hysteresis};
+///
+/// let config = ConfigBuilder::new()
+///     .alert_mode(AlertMode::Interrupt)
+///     .alert_control(AlertControl::Enabled)
+///     .hysteresis(Hysteresis::Deg_1_5C);
+/// assert_ne!(config, ConfigBuilder::default());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConfigBuilder {
+    alert_mode: AlertMode,
+    alert_polarity: AlertPolarity,
+    alert_select: AlertSelect,
+    alert_control: AlertControl,
+    window_lock: WindowLock,
+    critical_lock: CriticalLock,
+    shutdown_mode: ShutdownMode,
+    hysteresis: Hysteresis,
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        ConfigBuilder {
+            alert_mode: AlertMode::Comparator,
+            alert_polarity: AlertPolarity::ActiveLow,
+            alert_select: AlertSelect::All,
+            alert_control: AlertControl::Disabled,
+            window_lock: WindowLock::Unlocked,
+            critical_lock: CriticalLock::Unlocked,
+            shutdown_mode: ShutdownMode::Continuous,
+            hysteresis: Hysteresis::Deg_0_0C,
+        }
+    }
+}
+
+impl ConfigBuilder {
+    /// power-up defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// settings currently held by `reg`
+    pub fn from_register(reg: &ConfigurationRegister) -> Self {
+        ConfigBuilder {
+            alert_mode: reg.get_alert_mode(),
+            alert_polarity: reg.get_alert_polarity(),
+            alert_select: reg.get_alert_select(),
+            alert_control: reg.get_alert_control(),
+            window_lock: reg.get_window_lock(),
+            critical_lock: reg.get_critical_lock(),
+            shutdown_mode: reg.get_shutdown_mode(),
+            hysteresis: reg.get_hysteresis(),
+        }
+    }
+
+    pub fn alert_mode(mut self, mode: AlertMode) -> Self {
+        self.alert_mode = mode;
+        self
+    }
+
+    pub fn alert_polarity(mut self, mode: AlertPolarity) -> Self {
+        self.alert_polarity = mode;
+        self
+    }
+
+    pub fn alert_select(mut self, mode: AlertSelect) -> Self {
+        self.alert_select = mode;
+        self
+    }
+
+    pub fn alert_control(mut self, mode: AlertControl) -> Self {
+        self.alert_control = mode;
+        self
+    }
+
+    /// can only be cleared by a power-on reset once written
+    pub fn window_lock(mut self, mode: WindowLock) -> Self {
+        self.window_lock = mode;
+        self
+    }
+
+    /// can only be cleared by a power-on reset once written
+    pub fn critical_lock(mut self, mode: CriticalLock) -> Self {
+        self.critical_lock = mode;
+        self
+    }
+
+    pub fn shutdown_mode(mut self, mode: ShutdownMode) -> Self {
+        self.shutdown_mode = mode;
+        self
+    }
+
+    pub fn hysteresis(mut self, mode: Hysteresis) -> Self {
+        self.hysteresis = mode;
+        self
+    }
+
+    /// register value for this configuration
+    pub fn build(&self) -> ConfigurationRegister {
+        let mut reg = new();
+        reg.set_alert_mode(self.alert_mode);
+        reg.set_alert_polarity(self.alert_polarity);
+        reg.set_alert_select(self.alert_select);
+        reg.set_alert_control(self.alert_control);
+        reg.set_window_lock(self.window_lock);
+        reg.set_critical_lock(self.critical_lock);
+        reg.set_shutdown_mode(self.shutdown_mode);
+        reg.set_hysteresis(self.hysteresis);
+        reg
+    }
+
+    /// fields in which `other` differs from this configuration
+    pub fn diff(&self, other: &ConfigBuilder) -> ConfigDiff {
+        ConfigDiff {
+            alert_mode: self.alert_mode != other.alert_mode,
+            alert_polarity: self.alert_polarity != other.alert_polarity,
+            alert_select: self.alert_select != other.alert_select,
+            alert_control: self.alert_control != other.alert_control,
+            window_lock: self.window_lock != other.window_lock,
+            critical_lock: self.critical_lock != other.critical_lock,
+            shutdown_mode: self.shutdown_mode != other.shutdown_mode,
+            hysteresis: self.hysteresis != other.hysteresis,
+        }
+    }
+}
+
+/// Configuration fields that differ, `true` means different.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConfigDiff {
+    pub alert_mode: bool,
+    pub alert_polarity: bool,
+    pub alert_select: bool,
+    pub alert_control: bool,
+    pub window_lock: bool,
+    pub critical_lock: bool,
+    pub shutdown_mode: bool,
+    pub hysteresis: bool,
+}
+
+impl ConfigDiff {
+    /// no field differs
+    pub fn is_empty(&self) -> bool {
+        *self == ConfigDiff::default()
+    }
+}
+
+impl ConfigBuilder {
+    /// Fails with the fields `actual`, as read back after a write, does not hold.
+    pub(crate) fn check_applied(&self, actual: &ConfigurationRegister) -> Result<(), ConfigDiff> {
+        let diff = self.diff(&ConfigBuilder::from_register(actual));
+        if !diff.is_empty() {
+            return Err(diff);
+        }
+        Ok(())
+    }
+}
+
+/// Lock that prevents a write
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lock {
+    /// tUPPER and tLOWER Window Lock, bit 6
+    Window,
+    /// tCRIT Lock, bit 7
+    Critical,
+}
+
+/// Lock bits of the configuration register, both stay set until the next power-on reset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LockState {
+    pub window: WindowLock,
+    pub critical: CriticalLock,
+}
+
+impl LockState {
+    pub const UNLOCKED: LockState = LockState {
+        window: WindowLock::Unlocked,
+        critical: CriticalLock::Unlocked,
+    };
+
+    pub fn from_register(reg: &ConfigurationRegister) -> Self {
+        LockState {
+            window: reg.get_window_lock(),
+            critical: reg.get_critical_lock(),
+        }
+    }
+
+    pub fn is_window_locked(&self) -> bool {
+        self.window == WindowLock::Locked
+    }
+
+    pub fn is_critical_locked(&self) -> bool {
+        self.critical == CriticalLock::Locked
+    }
+
+    /// the lock blocking bits that can not be altered when either lock is set
+    fn any(&self) -> Option<Lock> {
+        if self.is_window_locked() {
+            Some(Lock::Window)
+        } else if self.is_critical_locked() {
+            Some(Lock::Critical)
+        } else {
+            None
+        }
+    }
+
+    /// Checks whether the device would accept changing configuration `current` into `new`.
+    pub fn check_config(&self, current: &ConfigBuilder, new: &ConfigBuilder) -> Result<(), Lock> {
+        // locks can not be cleared
+        if self.is_window_locked() && new.window_lock == WindowLock::Unlocked {
+            return Err(Lock::Window);
+        }
+        if self.is_critical_locked() && new.critical_lock == CriticalLock::Unlocked {
+            return Err(Lock::Critical);
+        }
+
+        if self.is_window_locked() && current.alert_select != new.alert_select {
+            return Err(Lock::Window);
+        }
+
+        // shutdown can still be left while locked, but not entered
+        let enters_shutdown = current.shutdown_mode == ShutdownMode::Continuous
+            && new.shutdown_mode == ShutdownMode::Shutdown;
+        let changed = current.alert_mode != new.alert_mode
+            || current.alert_polarity != new.alert_polarity
+            || current.alert_control != new.alert_control
+            || current.hysteresis != new.hysteresis
+            || enters_shutdown;
+        match self.any() {
+            Some(lock) if changed => Err(lock),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks whether the device would accept writing `reg`, given configuration `current`.
+    pub(crate) fn check_write(current: &ConfigurationRegister, reg: &Register) -> Result<(), Lock> {
+        let locks = LockState::from_register(current);
+        match reg.get_ptr() {
+            REGISTER_PTR => locks.check_config(
+                &ConfigBuilder::from_register(current),
+                &ConfigBuilder::from_register(&ConfigurationRegister(*reg)),
+            ),
+            reg_temp_alert_upper::REGISTER_PTR | reg_temp_alert_lower::REGISTER_PTR
+                if locks.is_window_locked() =>
+            {
+                Err(Lock::Window)
+            }
+            reg_temp_alert_crit::REGISTER_PTR if locks.is_critical_locked() => Err(Lock::Critical),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fails if either lock is set, each of them blocks one of the limits.
+    pub(crate) fn check_limits(&self) -> Result<(), Lock> {
+        match self.any() {
+            Some(lock) => Err(lock),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks whether the device in configuration `current` could be woken for a single
+    /// conversion and be shut down again afterwards.
+    pub(crate) fn check_one_shot(current: &ConfigurationRegister) -> Result<(), Lock> {
+        let awake = ConfigBuilder::from_register(current).shutdown_mode(ShutdownMode::Continuous);
+        LockState::from_register(current)
+            .check_config(&awake, &awake.shutdown_mode(ShutdownMode::Shutdown))
+    }
+
+    /// whether writing to pointer `ptr` can be affected by a lock
+    pub(crate) fn is_lockable(ptr: u8) -> bool {
+        matches!(
+            ptr,
+            REGISTER_PTR
+                | reg_temp_alert_upper::REGISTER_PTR
+                | reg_temp_alert_lower::REGISTER_PTR
+                | reg_temp_alert_crit::REGISTER_PTR
+        )
+    }
+}
+
 /// dirty little helper where 0 is false, > 0 is true
 fn bool(val: isize) -> bool {
     val != 0
@@ -272,7 +619,7 @@ mod tests {
 
     #[test]
     fn hysteresis() {
-        let mut reg: Register = new();
+        let mut reg: ConfigurationRegister = new();
         assert_eq!(reg.get_hysteresis(), Hysteresis::Deg_0_0C);
 
         reg.set_hysteresis(Hysteresis::Deg_1_5C);
@@ -287,4 +634,77 @@ mod tests {
         reg.set_hysteresis(Hysteresis::Deg_0_0C);
         assert_eq!(reg.get_hysteresis(), Hysteresis::Deg_0_0C);
     }
+
+    #[test]
+    fn config_builder() {
+        let config = ConfigBuilder::new()
+            .alert_mode(AlertMode::Interrupt)
+            .alert_polarity(AlertPolarity::ActiveHigh)
+            .alert_control(AlertControl::Enabled)
+            .shutdown_mode(ShutdownMode::Shutdown)
+            .hysteresis(Hysteresis::Deg_3_0C);
+
+        let reg = config.build();
+        assert_eq!(reg.0.as_u16(), 0b0000_0101_0000_1011);
+        assert_eq!(ConfigBuilder::from_register(&reg), config);
+
+        assert_eq!(ConfigBuilder::default().build(), new());
+        assert!(config.diff(&config).is_empty());
+
+        let diff = config.diff(&ConfigBuilder::default());
+        assert!(diff.alert_mode && diff.alert_polarity && diff.hysteresis);
+        assert!(!diff.alert_select && !diff.window_lock && !diff.critical_lock);
+    }
+
+    #[test]
+    fn lock_rules() {
+        let current = ConfigBuilder::new().window_lock(WindowLock::Locked);
+        let locks = LockState::from_register(&current.build());
+        assert!(locks.is_window_locked() && !locks.is_critical_locked());
+
+        assert_eq!(locks.check_config(&current, &current), Ok(()));
+        assert_eq!(
+            locks.check_config(&current, &current.window_lock(WindowLock::Unlocked)),
+            Err(Lock::Window)
+        );
+        assert_eq!(
+            locks.check_config(&current, &current.hysteresis(Hysteresis::Deg_1_5C)),
+            Err(Lock::Window)
+        );
+        assert_eq!(
+            locks.check_config(&current, &current.alert_select(AlertSelect::TCritOnly)),
+            Err(Lock::Window)
+        );
+        assert_eq!(
+            locks.check_config(&current, &current.critical_lock(CriticalLock::Locked)),
+            Ok(())
+        );
+
+        let shutdown = current.shutdown_mode(ShutdownMode::Shutdown);
+        assert_eq!(locks.check_config(&current, &shutdown), Err(Lock::Window));
+        assert_eq!(locks.check_config(&shutdown, &current), Ok(()));
+
+        // alert select is only protected by the window lock
+        let current = ConfigBuilder::new().critical_lock(CriticalLock::Locked);
+        let locks = LockState::from_register(&current.build());
+        assert_eq!(
+            locks.check_config(&current, &current.alert_select(AlertSelect::TCritOnly)),
+            Ok(())
+        );
+        assert_eq!(
+            locks.check_config(&current, &current.alert_mode(AlertMode::Interrupt)),
+            Err(Lock::Critical)
+        );
+    }
+
+    #[test]
+    fn lock_limits() {
+        let window = ConfigBuilder::new().window_lock(WindowLock::Locked).build();
+        let upper = *crate::reg_temp_alert_upper::new().register();
+        let crit = *crate::reg_temp_alert_crit::new().register();
+
+        assert_eq!(LockState::check_write(&window, &upper), Err(Lock::Window));
+        assert_eq!(LockState::check_write(&window, &crit), Ok(()));
+        assert_eq!(LockState::check_write(&new(), &upper), Ok(()));
+    }
 }
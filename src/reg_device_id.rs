@@ -1,11 +1,10 @@
-use reg::Register;
-use prelude::Read;
+use crate::prelude::Read;
+use crate::reg::Register;
 
 const REGISTER_PTR: u8 = 0b0111;
 const REGISTER_SIZE: u8 = 2;
 const DEVICE_ID: u8 = 0x04;
 
-
 pub trait DeviceId: Read {
     /// is the deviceId what it should be?
     fn is_valid_device(&self) -> bool;
@@ -17,11 +16,25 @@ pub trait DeviceId: Read {
     fn get_device_rev(&self) -> u8;
 }
 
-pub fn new() -> Register {
-    Register::new(REGISTER_PTR, REGISTER_SIZE)
+/// Device ID and revision register, read-only.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DeviceIdRegister(Register);
+
+pub fn new() -> DeviceIdRegister {
+    DeviceIdRegister(Register::new_unchecked(REGISTER_PTR, REGISTER_SIZE))
+}
+
+impl Read for DeviceIdRegister {
+    fn register(&self) -> &Register {
+        &self.0
+    }
+
+    fn register_mut(&mut self) -> &mut Register {
+        &mut self.0
+    }
 }
 
-impl DeviceId for Register {
+impl DeviceId for DeviceIdRegister {
     /// are we talking to the right device?
     fn is_valid_device(&self) -> bool {
         self.get_device_id() == DEVICE_ID
@@ -29,11 +42,11 @@ impl DeviceId for Register {
 
     /// get device id
     fn get_device_id(&self) -> u8 {
-        self.get_msb()
+        self.0.get_msb()
     }
 
     /// get device revision
     fn get_device_rev(&self) -> u8 {
-        self.get_lsb().unwrap()
+        self.0.get_lsb().unwrap_or_default()
     }
-}
\ No newline at end of file
+}
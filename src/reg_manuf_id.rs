@@ -1,5 +1,5 @@
-use reg::Register;
-use prelude::Read;
+use crate::prelude::Read;
+use crate::reg::Register;
 
 const REGISTER_PTR: u8 = 0b0110;
 const MANUFACTURER_ID: u16 = 0x0054;
@@ -10,19 +10,32 @@ pub trait ManufacturerId: Read {
     fn is_valid_manufacturer(&self) -> bool;
 }
 
-/// construct from i2c buffer
-pub fn new() -> Register {
-    Register::new(REGISTER_PTR, REGISTER_SIZE)
+/// Manufacturer ID register, read-only.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ManufacturerIdRegister(Register);
+
+pub fn new() -> ManufacturerIdRegister {
+    ManufacturerIdRegister(Register::new_unchecked(REGISTER_PTR, REGISTER_SIZE))
 }
 
-impl ManufacturerId for Register {
+impl Read for ManufacturerIdRegister {
+    fn register(&self) -> &Register {
+        &self.0
+    }
+
+    fn register_mut(&mut self) -> &mut Register {
+        &mut self.0
+    }
+}
+
+impl ManufacturerId for ManufacturerIdRegister {
     fn get_manufacturer_id(&self) -> u16 {
         // buffer is guaranteed to be 2 bytes here
-        self.as_u16()
+        self.0.as_u16()
     }
 
     /// are we talking to the right device?
     fn is_valid_manufacturer(&self) -> bool {
         self.get_manufacturer_id() == MANUFACTURER_ID
     }
-}
\ No newline at end of file
+}
@@ -1,5 +1,6 @@
-use reg::Register;
-use prelude::Write;
+use crate::error::ValueError;
+use crate::prelude::{Read, Write};
+use crate::reg::Register;
 
 const REGISTER_PTR: u8 = 0b1000;
 const REGISTER_SIZE: u8 = 1;
@@ -18,52 +19,98 @@ pub enum ResolutionVal {
     Deg_0_0625C = 0b11,
 }
 
+impl ResolutionVal {
+    /// typical conversion time in milliseconds
+    pub fn conversion_time_ms(&self) -> u32 {
+        match self {
+            ResolutionVal::Deg_0_5C => 30,
+            ResolutionVal::Deg_0_25C => 65,
+            ResolutionVal::Deg_0_125C => 130,
+            ResolutionVal::Deg_0_0625C => 250,
+        }
+    }
+}
+
 pub trait Resolution: Write {
-    fn get_resolution(&self) -> ResolutionVal;
+    /// fails if unimplemented bits 7-2 are set
+    fn get_resolution(&self) -> Result<ResolutionVal, ValueError>;
     fn set_resolution(&mut self, p: ResolutionVal);
 }
 
-pub fn new() -> Register {
-    Register::new(REGISTER_PTR, REGISTER_SIZE)
+/// Temperature resolution register.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ResolutionRegister(Register);
+
+pub fn new() -> ResolutionRegister {
+    ResolutionRegister(Register::new_unchecked(REGISTER_PTR, REGISTER_SIZE))
+}
+
+/// resolution held by `reg`, if it is the resolution register
+pub(crate) fn resolution_of(reg: &Register) -> Option<ResolutionVal> {
+    if reg.get_ptr() != REGISTER_PTR {
+        return None;
+    }
+    ResolutionRegister(*reg).get_resolution().ok()
 }
 
-impl Resolution for Register {
-    fn get_resolution(&self) -> ResolutionVal {
-        let val: u8 = self.get_msb();
+impl Read for ResolutionRegister {
+    fn register(&self) -> &Register {
+        &self.0
+    }
+
+    fn register_mut(&mut self) -> &mut Register {
+        &mut self.0
+    }
+}
+
+impl Write for ResolutionRegister {}
+
+impl Resolution for ResolutionRegister {
+    fn get_resolution(&self) -> Result<ResolutionVal, ValueError> {
+        let val: u8 = self.0.get_msb();
         match val {
-            0b00 => ResolutionVal::Deg_0_5C,
-            0b01 => ResolutionVal::Deg_0_25C,
-            0b10 => ResolutionVal::Deg_0_125C,
-            0b11 => ResolutionVal::Deg_0_0625C,
-            _ => panic!("invalid resolution")
+            0b00 => Ok(ResolutionVal::Deg_0_5C),
+            0b01 => Ok(ResolutionVal::Deg_0_25C),
+            0b10 => Ok(ResolutionVal::Deg_0_125C),
+            0b11 => Ok(ResolutionVal::Deg_0_0625C),
+            _ => Err(ValueError::InvalidRegisterValue(val as u16)),
         }
     }
 
     fn set_resolution(&mut self, p: ResolutionVal) {
-        self.set_msb(p as u8);
+        self.0.set_msb(p as u8);
     }
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn resolution() {
-        let mut reg: Register = new();
-        assert_eq!(reg.get_resolution(), ResolutionVal::Deg_0_5C);
+        let mut reg: ResolutionRegister = new();
+        assert_eq!(reg.get_resolution().unwrap(), ResolutionVal::Deg_0_5C);
 
         reg.set_resolution(ResolutionVal::Deg_0_25C);
-        assert_eq!(reg.get_resolution(), ResolutionVal::Deg_0_25C);
+        assert_eq!(reg.get_resolution().unwrap(), ResolutionVal::Deg_0_25C);
 
         reg.set_resolution(ResolutionVal::Deg_0_125C);
-        assert_eq!(reg.get_resolution(), ResolutionVal::Deg_0_125C);
+        assert_eq!(reg.get_resolution().unwrap(), ResolutionVal::Deg_0_125C);
 
         reg.set_resolution(ResolutionVal::Deg_0_0625C);
-        assert_eq!(reg.get_resolution(), ResolutionVal::Deg_0_0625C);
+        assert_eq!(reg.get_resolution().unwrap(), ResolutionVal::Deg_0_0625C);
 
         reg.set_resolution(ResolutionVal::Deg_0_5C);
-        assert_eq!(reg.get_resolution(), ResolutionVal::Deg_0_5C);
+        assert_eq!(reg.get_resolution().unwrap(), ResolutionVal::Deg_0_5C);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn invalid_resolution() {
+        let mut reg: ResolutionRegister = new();
+        reg.0.set_msb(0b100);
+        assert!(matches!(
+            reg.get_resolution(),
+            Err(ValueError::InvalidRegisterValue(0b100))
+        ));
+    }
+}
@@ -1,5 +1,6 @@
-use reg::Register;
-use reg_temp_generic::ReadableTempRegister;
+use crate::prelude::Read;
+use crate::reg::Register;
+use crate::reg_temp_generic::ReadableTempRegister;
 
 const REGISTER_PTR: u8 = 0b0101;
 const REGISTER_SIZE: u8 = 2;
@@ -14,19 +15,35 @@ pub trait Temperature: ReadableTempRegister {
     fn is_alert_lower(&self) -> bool;
 }
 
-pub fn new() -> Register {
-    Register::new(REGISTER_PTR, REGISTER_SIZE)
+/// Ambient temperature register, read-only.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TemperatureRegister(Register);
+
+pub fn new() -> TemperatureRegister {
+    TemperatureRegister(Register::new_unchecked(REGISTER_PTR, REGISTER_SIZE))
 }
 
-impl Temperature for Register {
+impl Read for TemperatureRegister {
+    fn register(&self) -> &Register {
+        &self.0
+    }
+
+    fn register_mut(&mut self) -> &mut Register {
+        &mut self.0
+    }
+}
+
+impl ReadableTempRegister for TemperatureRegister {}
+
+impl Temperature for TemperatureRegister {
     fn is_alert_critical(&self) -> bool {
-        self.get_bit(BIT_ALERT_CRITICAL)
+        self.0.get_bit(BIT_ALERT_CRITICAL)
     }
     fn is_alert_upper(&self) -> bool {
-        self.get_bit(BIT_ALERT_UPPER)
+        self.0.get_bit(BIT_ALERT_UPPER)
     }
     fn is_alert_lower(&self) -> bool {
-        self.get_bit(BIT_ALERT_LOWER)
+        self.0.get_bit(BIT_ALERT_LOWER)
     }
 }
 
@@ -41,7 +58,7 @@ mod tests {
         let mut reg = new();
 
         assert_eq!(reg.is_alert_critical(), false);
-        reg.set_bit(BIT_ALERT_CRITICAL, true);
+        reg.0.update_bit(BIT_ALERT_CRITICAL, true);
         assert_eq!(reg.is_alert_critical(), true);
     }
 
@@ -50,7 +67,7 @@ mod tests {
         let mut reg = new();
 
         assert_eq!(reg.is_alert_upper(), false);
-        reg.set_bit(BIT_ALERT_UPPER, true);
+        reg.0.update_bit(BIT_ALERT_UPPER, true);
         assert_eq!(reg.is_alert_upper(), true);
     }
 
@@ -59,7 +76,7 @@ mod tests {
         let mut reg = new();
 
         assert_eq!(reg.is_alert_lower(), false);
-        reg.set_bit(BIT_ALERT_LOWER, true);
+        reg.0.update_bit(BIT_ALERT_LOWER, true);
         assert_eq!(reg.is_alert_lower(), true);
     }
 }
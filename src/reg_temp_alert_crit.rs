@@ -1,14 +1,35 @@
-use reg::Register;
-use reg_temp_generic::WritableTempRegister;
+use crate::prelude::{Read, Write};
+use crate::reg::Register;
+use crate::reg_temp_generic::{ReadableTempRegister, WritableTempRegister};
 
 // Critical Temperature Limit register
-const REGISTER_PTR: u8 = 0b0100;
+pub(crate) const REGISTER_PTR: u8 = 0b0100;
 const REGISTER_SIZE: u8 = 2;
 
-pub fn new() -> Register {
-    Register::new(REGISTER_PTR, REGISTER_SIZE)
+/// Critical temperature limit (TCRIT) register.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CriticalLimitRegister(Register);
+
+pub fn new() -> CriticalLimitRegister {
+    CriticalLimitRegister(Register::new_unchecked(REGISTER_PTR, REGISTER_SIZE))
+}
+
+impl Read for CriticalLimitRegister {
+    fn register(&self) -> &Register {
+        &self.0
+    }
+
+    fn register_mut(&mut self) -> &mut Register {
+        &mut self.0
+    }
 }
 
+impl Write for CriticalLimitRegister {}
+
+impl ReadableTempRegister for CriticalLimitRegister {}
+
+impl WritableTempRegister for CriticalLimitRegister {}
+
 pub trait CriticalTemperatureAlert: WritableTempRegister {}
 
-impl CriticalTemperatureAlert for Register {}
\ No newline at end of file
+impl CriticalTemperatureAlert for CriticalLimitRegister {}
@@ -1,13 +1,34 @@
-use reg::Register;
-use reg_temp_generic::WritableTempRegister;
+use crate::prelude::{Read, Write};
+use crate::reg::Register;
+use crate::reg_temp_generic::{ReadableTempRegister, WritableTempRegister};
 
 // Temperature Alert Upper Boundary and Lower Boundary Limit registers
-const REGISTER_PTR: u8 = 0b0011;
+pub(crate) const REGISTER_PTR: u8 = 0b0011;
 const REGISTER_SIZE: u8 = 2;
 
-pub fn new() -> Register {
-    Register::new(REGISTER_PTR, REGISTER_SIZE)
+/// Alert lower boundary (TLOWER) register.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LowerLimitRegister(Register);
+
+pub fn new() -> LowerLimitRegister {
+    LowerLimitRegister(Register::new_unchecked(REGISTER_PTR, REGISTER_SIZE))
+}
+
+impl Read for LowerLimitRegister {
+    fn register(&self) -> &Register {
+        &self.0
+    }
+
+    fn register_mut(&mut self) -> &mut Register {
+        &mut self.0
+    }
 }
 
+impl Write for LowerLimitRegister {}
+
+impl ReadableTempRegister for LowerLimitRegister {}
+
+impl WritableTempRegister for LowerLimitRegister {}
+
 pub trait LowerTemperatureAlert: WritableTempRegister {}
-impl LowerTemperatureAlert for Register {}
\ No newline at end of file
+impl LowerTemperatureAlert for LowerLimitRegister {}
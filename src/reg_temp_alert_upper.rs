@@ -1,14 +1,35 @@
-use reg::Register;
-use reg_temp_generic::WritableTempRegister;
+use crate::prelude::{Read, Write};
+use crate::reg::Register;
+use crate::reg_temp_generic::{ReadableTempRegister, WritableTempRegister};
 
 // Temperature Alert Upper Boundary and Lower Boundary Limit registers
-const REGISTER_PTR: u8 = 0b0010;
+pub(crate) const REGISTER_PTR: u8 = 0b0010;
 const REGISTER_SIZE: u8 = 2;
 
-pub fn new() -> Register {
-    Register::new(REGISTER_PTR, REGISTER_SIZE)
+/// Alert upper boundary (TUPPER) register.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UpperLimitRegister(Register);
+
+pub fn new() -> UpperLimitRegister {
+    UpperLimitRegister(Register::new_unchecked(REGISTER_PTR, REGISTER_SIZE))
+}
+
+impl Read for UpperLimitRegister {
+    fn register(&self) -> &Register {
+        &self.0
+    }
+
+    fn register_mut(&mut self) -> &mut Register {
+        &mut self.0
+    }
 }
 
+impl Write for UpperLimitRegister {}
+
+impl ReadableTempRegister for UpperLimitRegister {}
+
+impl WritableTempRegister for UpperLimitRegister {}
+
 pub trait UpperTemperatureAlert: WritableTempRegister {}
 
-impl UpperTemperatureAlert for Register {}
\ No newline at end of file
+impl UpperTemperatureAlert for UpperLimitRegister {}
@@ -1,192 +1,103 @@
-extern crate cast;
-
-#[cfg(feature = "with_floating_point")]
-use cast::f32;
-#[cfg(feature = "with_floating_point")]
-use core::f32;
-
-use cast::i16;
-use prelude::Read;
-use prelude::Write;
-use reg::Register;
-use reg_res::ResolutionVal;
+use crate::celsius::Celsius;
+use crate::error::ValueError;
+use crate::prelude::Read;
+use crate::prelude::Write;
+use crate::reg::Register;
+use crate::reg_res::ResolutionVal;
 
 const RANGE_LIMIT: i16 = 256;
-const BIT_SIGN: u8 = 0x10;
+/// bit 12-0: sign and temperature
+const MASK_TEMP: u16 = 0x1fff;
+/// bit 1-0 of the limit registers are unimplemented
+pub(crate) const MASK_LIMIT_FRACT: i16 = 0b11;
 
 /// internal generic trait for temperature registers
 ///
-/// bit 15-13 Unimplemented: Read as ‘0’
-/// bit 12 Sign: Sign bit (0 = TA ≥ 0°C, 1 = TA < 0°C)
-/// bit 11-2 tUPPER/tLOWER/tCRIT: Temperature Boundary bits in two’s complement format.
-/// 2-3 fractional part, 4-11 decimal part
-/// bit 1-0 Unimplemented: Read as ‘0
+/// bit 15-13 TA: alert flags, tUPPER/tLOWER/tCRIT: Unimplemented, read as ‘0’
+/// bit 12 Sign: Sign bit (0 = T ≥ 0°C, 1 = T < 0°C)
+/// bit 11-0 Temperature in two’s complement format together with the sign bit.
+/// 4-11 integer part, 0-3 fractional part in 1/16 °C
+/// tUPPER/tLOWER/tCRIT: bit 1-0 Unimplemented, read as ‘0’, so limits are 0.25 °C steps
 pub trait ReadableTempRegister: Read {
-    /// degree celsius as float
-    #[cfg(feature = "with_floating_point")]
-    fn get_celsius(&self, res: ResolutionVal) -> f32;
-
-    /// avoids floats, but only works up to 0.125 resolution
-    fn get_milli_celsius(&self, res: ResolutionVal) -> i32;
-
-    /// raw register value
-    fn get_raw_value(&self) -> u16;
-}
-
-impl ReadableTempRegister for Register {
-    #[cfg(feature = "with_floating_point")]
-    fn get_celsius(&self, res: ResolutionVal) -> f32 {
-        let high = self.get_msb() & 0x1f; // clear flags
-        let low: u8 = self.get_lsb().unwrap();
-
-        let temp_dec = get_decimal_part(high, low);
-
-        let mut ftemp = f32(temp_dec);
-        ftemp += get_fractional_part_float(res, low);
-        ftemp
+    /// temperature, bits finer than the resolution are masked off
+    fn get_celsius(&self, res: ResolutionVal) -> Celsius {
+        let raw = decode(self.register().as_u16()).as_sixteenths();
+        let unused_bits = 3 - res as u16;
+        Celsius::from_sixteenths(raw & !((1 << unused_bits) - 1))
     }
 
+    /// avoids floats, rounded to the nearest m°C for 0.0625 °C resolution like
+    /// `Celsius::to_millicelsius()`
     fn get_milli_celsius(&self, res: ResolutionVal) -> i32 {
-        if res == ResolutionVal::Deg_0_0625C {
-            panic!("precision invalid for milli C°")
-        }
-        let high = self.get_msb() & 0x1f; // clear flags
-        let low: u8 = self.get_lsb().unwrap();
-
-        let mut temp_dec = get_decimal_part(high, low) as i32 * 1000;
-        temp_dec += get_fractional_part_dec(res, low) as i32;
-        temp_dec
+        self.get_celsius(res).to_millicelsius()
     }
 
+    /// raw register value
     fn get_raw_value(&self) -> u16 {
-        self.as_u16()
+        self.register().as_u16()
     }
 }
 
-pub trait WritableTempRegister: ReadableTempRegister + Write {
-    #[cfg(feature = "with_floating_point")]
-    fn set_celsius(&mut self, val: f32);
-
-    fn set_milli_celsius(&mut self, val: i32);
-}
-
-impl WritableTempRegister for Register {
-    #[cfg(feature = "with_floating_point")]
-    fn set_celsius(&mut self, val: f32) {
-        if val >= f32(RANGE_LIMIT) || val <= -f32(RANGE_LIMIT) {
-            panic!(
-                "temperature {} exceeds valid range of +-{}",
-                val, RANGE_LIMIT
-            )
-        }
-
-        let temp_dec: u16 = val as u16;
-        let mut high = (temp_dec / 16) as u8;
-        let mut low = (temp_dec * 16) as u8;
-
-        if val < 0.0 {
-            high |= BIT_SIGN; // set sign bit
-        }
+impl ReadableTempRegister for Register {}
 
-        // fract() is std only
-        let fract: f32 = val - (temp_dec as f32);
-        let mut fract_bits: u8 =
-            (fract / get_precision_factor_float(ResolutionVal::Deg_0_0625C)) as u8;
-        fract_bits &= 0b1100; // mask bit 0+1
-        low += fract_bits;
-
-        self.set_msb(high);
-        self.set_lsb(low);
+pub trait WritableTempRegister: ReadableTempRegister + Write {
+    /// rounds down to the next 0.25 °C step, fails outside of ±256 °C
+    fn set_celsius(&mut self, val: Celsius) -> Result<(), ValueError> {
+        let raw = encode_limit(val)?;
+        self.register_mut().set_u16(raw);
+        Ok(())
     }
 
-    fn set_milli_celsius(&mut self, val: i32) {
-        if val.abs() >= 1000 * RANGE_LIMIT as i32 {
-            panic!(
-                "temperature {} exceeds valid range of +-{}",
-                val, RANGE_LIMIT
-            )
+    fn set_milli_celsius(&mut self, val: i32) -> Result<(), ValueError> {
+        let limit = 1000 * RANGE_LIMIT as i32;
+        if val <= -limit || val >= limit {
+            return Err(ValueError::OutOfRange);
         }
-
-        let temp_dec: i32 = val / 1000;
-        let mut high = (temp_dec / 16) as u8;
-        let mut low = (temp_dec * 16) as u8;
-
-        if val < 0 {
-            high |= BIT_SIGN; // set sign bit
-        }
-
-        // fract() is std only
-        let fract: i32 = val - (temp_dec * 1000);
-        let mut fract_bits: u8 =
-            (fract / get_precision_factor_dec(ResolutionVal::Deg_0_0625C) as i32) as u8;
-        fract_bits &= 0b1100; // mask bit 0+1
-        low += fract_bits;
-
-        self.set_msb(high);
-        self.set_lsb(low);
-    }
-}
-
-fn get_decimal_part(mut high: u8, low: u8) -> i16 {
-    high &= 0x1f; // clear flags
-
-    // sign bit set, < 0°C
-    if high & BIT_SIGN == BIT_SIGN {
-        high &= 0x0f; // clear sign bit
-        256 - (i16(high) * 16 + i16(low) / 16)
-    } else {
-        i16(high) * 16 + i16(low) / 16
+        self.set_celsius(Celsius::from_millicelsius(val))
     }
 }
 
-fn get_fractional_part_dec(res: ResolutionVal, low: u8) -> u16 {
-    let fract: u16 = (low & 0x000F).into(); // mask nibble
-    (fract >> (3 - res as u16)) * get_precision_factor_dec(res)
-}
+impl WritableTempRegister for Register {}
 
-#[cfg(feature = "with_floating_point")]
-fn get_fractional_part_float(res: ResolutionVal, low: u8) -> f32 {
-    let fract = low & 0x000F; // mask nibble
-    f32(fract >> (3 - res as u8)) * get_precision_factor_float(res)
+/// Decodes the 13 bit two's complement of the ambient and limit registers, flag bits are ignored.
+pub fn decode(val: u16) -> Celsius {
+    // sign-extend bit 12
+    Celsius::from_sixteenths(((val & MASK_TEMP) << 3) as i16 >> 3)
 }
 
-//fn set_fractional_part_float(res: ResolutionVal, val: f32) {}
-//fn set_fractional_part_dec(res: ResolutionVal, val: i32) {}
-
-#[cfg(feature = "with_floating_point")]
-fn get_precision_factor_float(res: ResolutionVal) -> f32 {
-    match res {
-        ResolutionVal::Deg_0_0625C => 0.0625,
-        ResolutionVal::Deg_0_125C => 0.125,
-        ResolutionVal::Deg_0_25C => 0.25,
-        ResolutionVal::Deg_0_5C => 0.5,
+/// Encodes a limit register value as 13 bit two's complement.
+///
+/// Limits are 0.25 °C steps, finer bits are dropped which rounds down, also for negative values.
+/// Fails outside of ±256 °C.
+pub fn encode_limit(val: Celsius) -> Result<u16, ValueError> {
+    let limit = Celsius::from_degrees(RANGE_LIMIT);
+    if val <= -limit || val >= limit {
+        return Err(ValueError::OutOfRange);
     }
-}
 
-fn get_precision_factor_dec(res: ResolutionVal) -> u16 {
-    match res {
-        ResolutionVal::Deg_0_0625C => 62,
-        ResolutionVal::Deg_0_125C => 125,
-        ResolutionVal::Deg_0_25C => 250,
-        ResolutionVal::Deg_0_5C => 500,
-    }
+    let raw = val.as_sixteenths() & !MASK_LIMIT_FRACT;
+    Ok(raw as u16 & MASK_TEMP)
 }
 
 #[cfg(test)]
 mod tests {
     /// prevent auto-format fuckup
     use super::*;
-    use reg_res::ResolutionVal;
+    use crate::reg_res::ResolutionVal;
+
+    fn celsius(mc: i32) -> Celsius {
+        Celsius::from_millicelsius(mc)
+    }
 
     #[test]
     fn read_sensor_value() {
         let msb: u8 = 0b00000001;
         let lsb: u8 = 0b10010100;
-        let mut reg = Register::new(1, 2);
+        let mut reg = Register::new(1, 2).unwrap();
         reg.set_buf([msb, lsb]);
 
         let temp = reg.get_celsius(ResolutionVal::Deg_0_0625C);
-        assert_eq!(temp, 25.25);
+        assert_eq!(temp, celsius(25250));
 
         let temp = reg.get_milli_celsius(ResolutionVal::Deg_0_125C);
         assert_eq!(temp, 25250);
@@ -194,15 +105,15 @@ mod tests {
 
     #[test]
     fn set_celsius_integer() {
-        let mut reg = Register::new(1, 2);
+        let mut reg = Register::new(1, 2).unwrap();
 
         // example bit pattern taken from data sheet, page 23
-        reg.set_celsius(90.0);
+        reg.set_celsius(celsius(90000)).unwrap();
         assert_eq!(0b00000101, reg.get_msb());
         assert_eq!(0b10100000, reg.get_lsb().unwrap());
 
         let temp = reg.get_celsius(ResolutionVal::Deg_0_25C);
-        assert_eq!(temp, 90.00);
+        assert_eq!(temp, celsius(90000));
 
         let temp = reg.get_milli_celsius(ResolutionVal::Deg_0_25C);
         assert_eq!(temp, 90000);
@@ -210,35 +121,35 @@ mod tests {
 
     #[test]
     fn set_celsius_fractional() {
-        let mut reg = Register::new(1, 2);
+        let mut reg = Register::new(1, 2).unwrap();
 
         // example bit pattern taken from data sheet, page 23
-        reg.set_celsius(90.75);
+        reg.set_celsius(celsius(90750)).unwrap();
         assert_eq!(0b00000101, reg.get_msb());
         assert_eq!(0b10101100, reg.get_lsb().unwrap());
 
         let temp = reg.get_celsius(ResolutionVal::Deg_0_25C);
-        assert_eq!(temp, 90.75);
+        assert_eq!(temp, celsius(90750));
 
         let temp = reg.get_milli_celsius(ResolutionVal::Deg_0_25C);
         assert_eq!(temp, 90750);
 
-        reg.set_celsius(90.25);
+        reg.set_celsius(celsius(90250)).unwrap();
         assert_eq!(0b00000101, reg.get_msb());
         assert_eq!(0b10100100, reg.get_lsb().unwrap());
     }
 
     #[test]
     fn set_milli_celsius() {
-        let mut reg = Register::new(1, 2);
+        let mut reg = Register::new(1, 2).unwrap();
 
         // example bit pattern taken from data sheet, page 23
-        reg.set_milli_celsius(90000);
+        reg.set_milli_celsius(90000).unwrap();
         assert_eq!(0b00000101, reg.get_msb());
         assert_eq!(0b10100000, reg.get_lsb().unwrap());
 
         let temp = reg.get_celsius(ResolutionVal::Deg_0_25C);
-        assert_eq!(temp, 90.00);
+        assert_eq!(temp, celsius(90000));
 
         let temp = reg.get_milli_celsius(ResolutionVal::Deg_0_25C);
         assert_eq!(temp, 90000);
@@ -246,17 +157,149 @@ mod tests {
 
     #[test]
     fn set_milli_celsius_fractional() {
-        let mut reg = Register::new(1, 2);
+        let mut reg = Register::new(1, 2).unwrap();
 
         // example bit pattern taken from data sheet, page 23
-        reg.set_milli_celsius(90250);
+        reg.set_milli_celsius(90250).unwrap();
         assert_eq!(0b00000101, reg.get_msb());
         assert_eq!(0b10100100, reg.get_lsb().unwrap());
 
         let temp = reg.get_celsius(ResolutionVal::Deg_0_25C);
-        assert_eq!(temp, 90.25);
+        assert_eq!(temp, celsius(90250));
 
         let temp = reg.get_milli_celsius(ResolutionVal::Deg_0_25C);
         assert_eq!(temp, 90250);
     }
+
+    #[test]
+    fn invalid_input() {
+        let mut reg = Register::new(1, 2).unwrap();
+
+        assert!(matches!(
+            reg.set_celsius(celsius(256000)),
+            Err(ValueError::OutOfRange)
+        ));
+        assert!(matches!(
+            reg.set_celsius(celsius(-256000)),
+            Err(ValueError::OutOfRange)
+        ));
+        assert!(matches!(
+            reg.set_milli_celsius(256000),
+            Err(ValueError::OutOfRange)
+        ));
+        assert!(matches!(
+            reg.set_milli_celsius(i32::MIN),
+            Err(ValueError::OutOfRange)
+        ));
+    }
+
+    #[test]
+    fn milli_celsius_sixteenths() {
+        let mut reg = Register::new(5, 2).unwrap();
+        reg.set_buf([0x01, 0x93]);
+        let temp = reg.get_milli_celsius(ResolutionVal::Deg_0_0625C);
+        assert_eq!(
+            temp,
+            reg.get_celsius(ResolutionVal::Deg_0_0625C)
+                .to_millicelsius()
+        );
+        assert_eq!(temp, 25_188);
+
+        reg.set_buf([0x1f, 0xff]);
+        let temp = reg.get_milli_celsius(ResolutionVal::Deg_0_0625C);
+        assert_eq!(temp, -63);
+    }
+
+    /// 1/16 °C according to the datasheet equation for TA, independent of `decode()`
+    fn datasheet_sixteenths(msb: u8, lsb: u8) -> i16 {
+        let upper = (msb & 0x1f) as i16; // clear flags
+        if upper & 0x10 == 0x10 {
+            // TA = (UpperByte x 2^4 + LowerByte x 2^-4) - 256, sign cleared
+            ((upper & 0x0f) << 8 | lsb as i16) - 4096
+        } else {
+            upper << 8 | lsb as i16
+        }
+    }
+
+    #[test]
+    fn known_values() {
+        let cases = [
+            (125_000, 0x07d0),
+            (90_000, 0x05a0),
+            (25_250, 0x0194),
+            (250, 0x0004),
+            (0, 0x0000),
+            (-250, 0x1ffc),
+            (-10_000, 0x1f60),
+            (-25_250, 0x1e6c),
+            (-40_000, 0x1d80),
+        ];
+        for (mc, raw) in cases {
+            let mut reg = Register::new(2, 2).unwrap();
+            reg.set_milli_celsius(mc).unwrap();
+            assert_eq!(reg.get_raw_value(), raw, "{} m°C", mc);
+            assert_eq!(decode(raw), celsius(mc));
+            // alert flags do not change the temperature
+            assert_eq!(decode(raw | 0xe000), celsius(mc));
+        }
+    }
+
+    #[test]
+    fn ambient_round_trip() {
+        // every 1/16 °C step from -40 °C to +125 °C
+        for raw in -40 * 16..=125 * 16 {
+            let bits = (raw as i32).rem_euclid(0x2000) as u16;
+            let [msb, lsb] = (bits | 0xe000).to_be_bytes();
+
+            let mut reg = Register::new(5, 2).unwrap();
+            reg.set_buf([msb, lsb]);
+
+            let temp = reg.get_celsius(ResolutionVal::Deg_0_0625C);
+            assert_eq!(temp.as_sixteenths(), raw);
+            assert_eq!(temp.as_sixteenths(), datasheet_sixteenths(msb, lsb));
+        }
+    }
+
+    #[test]
+    fn limit_round_trip() {
+        // every 0.25 °C step from -40 °C to +125 °C
+        for raw in (-40 * 16..=125 * 16).step_by(4) {
+            let temp = Celsius::from_sixteenths(raw);
+            let mut reg = Register::new(2, 2).unwrap();
+            reg.set_celsius(temp).unwrap();
+
+            let [msb, lsb] = reg.get_raw_value().to_be_bytes();
+            assert_eq!(reg.get_raw_value() & 0b11, 0);
+            assert_eq!(datasheet_sixteenths(msb, lsb), raw);
+            assert_eq!(reg.get_celsius(ResolutionVal::Deg_0_25C), temp);
+        }
+    }
+
+    #[test]
+    fn limit_quantization() {
+        let mut reg = Register::new(2, 2).unwrap();
+
+        reg.set_milli_celsius(10_100).unwrap();
+        assert_eq!(reg.get_milli_celsius(ResolutionVal::Deg_0_25C), 10_000);
+
+        reg.set_milli_celsius(-10_100).unwrap();
+        assert_eq!(reg.get_milli_celsius(ResolutionVal::Deg_0_25C), -10_250);
+    }
+
+    #[test]
+    fn resolution_masking_below_zero() {
+        let mut reg = Register::new(5, 2).unwrap();
+        // -0.0625 °C
+        reg.set_buf([0x1f, 0xff]);
+
+        assert_eq!(
+            reg.get_celsius(ResolutionVal::Deg_0_0625C),
+            Celsius::from_sixteenths(-1)
+        );
+        assert_eq!(
+            reg.get_celsius(ResolutionVal::Deg_0_5C),
+            Celsius::from_sixteenths(-8)
+        );
+        assert_eq!(reg.get_milli_celsius(ResolutionVal::Deg_0_125C), -125);
+    }
 }
@@ -0,0 +1,477 @@
+//! Simulated MCP9808 for tests without hardware
+//!
+//! `SimulatedMcp9808` implements `embedded_hal::i2c::I2c` on top of a complete register file.
+//! Conversions happen when the temperature is set or on `tick()`, never on register writes, alert
+//! flags and output follow `alert::AlertPredictor`.
+
+use crate::address::SlaveAddress;
+use crate::alert::{AlertPrediction, AlertPredictor};
+use crate::celsius::Celsius;
+use crate::prelude::Read;
+use crate::{reg_conf, reg_temp_alert_crit, reg_temp_alert_lower, reg_temp_alert_upper};
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+
+const PTR_CONFIG: u8 = 0b0001;
+const PTR_UPPER: u8 = 0b0010;
+const PTR_LOWER: u8 = 0b0011;
+const PTR_CRIT: u8 = 0b0100;
+const PTR_TEMP: u8 = 0b0101;
+const PTR_MANUF_ID: u8 = 0b0110;
+const PTR_DEVICE_ID: u8 = 0b0111;
+const PTR_RESOLUTION: u8 = 0b1000;
+/// bit 3-0 of the pointer register select the register
+const MASK_PTR: u8 = 0x0f;
+
+const MANUFACTURER_ID: u16 = 0x0054;
+const DEVICE_ID: u16 = 0x0400;
+
+/// bit 15-11 of the configuration register are reserved
+const MASK_CONFIG: u16 = 0x07ff;
+const BIT_ALERT_STATUS: u16 = 1 << 4;
+const BIT_INT_CLEAR: u16 = 1 << 5;
+const BIT_WINDOW_LOCK: u16 = 1 << 6;
+const BIT_CRIT_LOCK: u16 = 1 << 7;
+const BIT_SHUTDOWN: u16 = 1 << 8;
+const BIT_ALERT_SELECT: u16 = 1 << 2;
+/// alert mode, polarity, control and hysteresis can not be altered while either lock is set
+const MASK_LOCKED: u16 = 0b110_0000_1011;
+/// bit 12-2 of the limit registers are implemented
+const MASK_LIMIT: u16 = 0x1ffc;
+/// bit 12-0 of the ambient temperature register
+const MASK_TEMP: u16 = 0x1fff;
+const MASK_RESOLUTION: u8 = 0b11;
+
+/// Simulated MCP9808, driven by the temperature a test sets.
+///
+/// # Example
+///
+/// ```
+/// use mcp9808::address::SlaveAddress;
+/// use mcp9808::celsius::Celsius;
+/// use mcp9808::sim::SimulatedMcp9808;
+/// use mcp9808::MCP9808;
+///
+/// let mut sim = SimulatedMcp9808::new(SlaveAddress::Default);
+/// sim.set_temperature(Celsius::from_millicelsius(21_250));
+///
+/// let mut dev = MCP9808::probe(sim, SlaveAddress::Default).unwrap();
+/// assert_eq!(dev.read_millicelsius().unwrap(), 21_250);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SimulatedMcp9808 {
+    addr: u8,
+    pointer: u8,
+    config: u16,
+    upper: u16,
+    lower: u16,
+    critical: u16,
+    resolution: u8,
+    /// temperature of the environment
+    temperature: Celsius,
+    /// result of the last conversion, without flags
+    ambient: u16,
+    alert: AlertPredictor,
+}
+
+impl SimulatedMcp9808 {
+    /// Device in its power-up state at 0 °C.
+    pub fn new(addr: SlaveAddress) -> Self {
+        let mut sim = SimulatedMcp9808 {
+            addr: addr.into(),
+            pointer: 0,
+            config: 0,
+            upper: 0,
+            lower: 0,
+            critical: 0,
+            resolution: MASK_RESOLUTION,
+            temperature: Celsius::ZERO,
+            ambient: 0,
+            alert: AlertPredictor::new(
+                &reg_conf::new(),
+                &reg_temp_alert_lower::new(),
+                &reg_temp_alert_upper::new(),
+                &reg_temp_alert_crit::new(),
+            ),
+        };
+        sim.convert();
+        sim
+    }
+
+    /// Resets all registers including the lock bits, keeps the temperature.
+    pub fn power_on_reset(&mut self) {
+        let temperature = self.temperature;
+        *self = SimulatedMcp9808 {
+            addr: self.addr,
+            ..SimulatedMcp9808::new(SlaveAddress::Default)
+        };
+        self.set_temperature(temperature);
+    }
+
+    /// Changes the temperature of the environment, converted right away unless in shutdown.
+    pub fn set_temperature(&mut self, temp: Celsius) {
+        self.temperature = temp;
+        self.convert();
+    }
+
+    /// One conversion of the current temperature, as the device does every tCONV.
+    ///
+    /// New limits, resolution and alert settings take effect here, not when they are written.
+    pub fn tick(&mut self) {
+        self.convert();
+    }
+
+    pub fn temperature(&self) -> Celsius {
+        self.temperature
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.config & BIT_SHUTDOWN != 0
+    }
+
+    /// register the pointer currently selects
+    pub fn pointer(&self) -> u8 {
+        self.pointer
+    }
+
+    /// flags and ALERT output after the last conversion
+    pub fn alert(&self) -> AlertPrediction {
+        self.alert.state()
+    }
+
+    /// Value a read of register `ptr` would return.
+    pub fn register(&self, ptr: u8) -> u16 {
+        match ptr & MASK_PTR {
+            PTR_CONFIG => {
+                let status = if self.alert.state().asserted {
+                    BIT_ALERT_STATUS
+                } else {
+                    0
+                };
+                self.config | status
+            }
+            PTR_UPPER => self.upper,
+            PTR_LOWER => self.lower,
+            PTR_CRIT => self.critical,
+            PTR_TEMP => {
+                let alert = self.alert.state();
+                self.ambient
+                    | (alert.critical as u16) << 15
+                    | (alert.upper as u16) << 14
+                    | (alert.lower as u16) << 13
+            }
+            PTR_MANUF_ID => MANUFACTURER_ID,
+            PTR_DEVICE_ID => DEVICE_ID,
+            PTR_RESOLUTION => self.resolution as u16,
+            _ => 0,
+        }
+    }
+
+    fn is_locked(&self) -> bool {
+        self.config & (BIT_WINDOW_LOCK | BIT_CRIT_LOCK) != 0
+    }
+
+    /// Writes `data` to register `ptr`, like the device ignoring read-only and locked bits.
+    fn write_register(&mut self, ptr: u8, data: &[u8]) {
+        let val = match *data {
+            [msb, lsb, ..] => u16::from_be_bytes([msb, lsb]),
+            [byte] if ptr == PTR_RESOLUTION => byte as u16,
+            // incomplete write
+            _ => return,
+        };
+        let window_locked = self.config & BIT_WINDOW_LOCK != 0;
+        let crit_locked = self.config & BIT_CRIT_LOCK != 0;
+        match ptr {
+            PTR_CONFIG => self.write_config(val),
+            PTR_UPPER if !window_locked => self.upper = val & MASK_LIMIT,
+            PTR_LOWER if !window_locked => self.lower = val & MASK_LIMIT,
+            PTR_CRIT if !crit_locked => self.critical = val & MASK_LIMIT,
+            PTR_RESOLUTION => {
+                if let Some(&byte) = data.first() {
+                    self.resolution = byte & MASK_RESOLUTION;
+                }
+            }
+            _ => return,
+        }
+        self.reconfigure_alert();
+    }
+
+    fn write_config(&mut self, val: u16) {
+        let old = self.config;
+        let mut new = val & MASK_CONFIG;
+
+        if self.is_locked() {
+            new = new & !MASK_LOCKED | old & MASK_LOCKED;
+            // shutdown can be left, not entered
+            if old & BIT_SHUTDOWN == 0 {
+                new &= !BIT_SHUTDOWN;
+            }
+        }
+        if old & BIT_WINDOW_LOCK != 0 {
+            new = new & !BIT_ALERT_SELECT | old & BIT_ALERT_SELECT | BIT_WINDOW_LOCK;
+        }
+        new |= old & BIT_CRIT_LOCK;
+
+        // reads as 0, status is read-only
+        self.config = new & !(BIT_INT_CLEAR | BIT_ALERT_STATUS);
+        self.reconfigure_alert();
+        if new & BIT_INT_CLEAR != 0 {
+            self.alert.clear_interrupt();
+        }
+    }
+
+    fn reconfigure_alert(&mut self) {
+        let mut conf = reg_conf::new();
+        conf.register_mut().set_u16(self.config);
+        let mut lower = reg_temp_alert_lower::new();
+        lower.register_mut().set_u16(self.lower);
+        let mut upper = reg_temp_alert_upper::new();
+        upper.register_mut().set_u16(self.upper);
+        let mut critical = reg_temp_alert_crit::new();
+        critical.register_mut().set_u16(self.critical);
+        self.alert.reconfigure(&conf, &lower, &upper, &critical);
+    }
+
+    /// one conversion at the configured resolution, nothing happens in shutdown
+    fn convert(&mut self) {
+        if self.is_shutdown() {
+            return;
+        }
+        let unused_bits = 3 - self.resolution;
+        let raw = self.temperature.as_sixteenths() & !((1 << unused_bits) - 1);
+        self.ambient = raw as u16 & MASK_TEMP;
+        self.alert.step(Celsius::from_sixteenths(raw));
+    }
+
+    /// bytes a read returns, starting at the current pointer
+    fn read_bytes(&self) -> [u8; 2] {
+        let val = self.register(self.pointer);
+        if self.pointer == PTR_RESOLUTION {
+            [val as u8, 0]
+        } else {
+            val.to_be_bytes()
+        }
+    }
+}
+
+impl ErrorType for SimulatedMcp9808 {
+    type Error = ErrorKind;
+}
+
+impl I2c for SimulatedMcp9808 {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if address != self.addr {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+
+        // adjacent writes form one write: pointer followed by up to two data bytes
+        let mut write = [0u8; 3];
+        let mut len = 0;
+        let mut ops = operations.iter_mut().peekable();
+        while let Some(op) = ops.next() {
+            match op {
+                Operation::Write(bytes) => {
+                    for byte in bytes.iter() {
+                        if let Some(dst) = write.get_mut(len) {
+                            *dst = *byte;
+                            len += 1;
+                        }
+                    }
+                    if matches!(ops.peek(), Some(Operation::Write(_))) {
+                        continue;
+                    }
+                    if let Some((ptr, data)) = write.get(..len).and_then(|w| w.split_first()) {
+                        self.pointer = ptr & MASK_PTR;
+                        self.write_register(self.pointer, data);
+                    }
+                    len = 0;
+                }
+                Operation::Read(buf) => {
+                    let bytes = self.read_bytes();
+                    for (dst, src) in buf.iter_mut().zip(bytes.iter().chain([0u8].iter().cycle())) {
+                        *dst = *src;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::interface::RegisterInterface;
+    use crate::prelude::Read;
+    use crate::reg_conf::{
+        AlertControl, AlertMode, ConfigBuilder, Configuration, Hysteresis, Lock, ShutdownMode,
+    };
+    use crate::reg_res::ResolutionVal;
+    use crate::MCP9808;
+    use core::cell::RefCell;
+    use embedded_hal::delay::DelayNs;
+
+    /// bus and delay on one device, every delay lasts one conversion
+    struct Shared<'a>(&'a RefCell<SimulatedMcp9808>);
+
+    impl ErrorType for Shared<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for Shared<'_> {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.0.borrow_mut().transaction(address, operations)
+        }
+    }
+
+    impl DelayNs for Shared<'_> {
+        fn delay_ns(&mut self, _ns: u32) {
+            self.0.borrow_mut().tick();
+        }
+    }
+
+    fn driver(temp: Celsius) -> MCP9808<SimulatedMcp9808> {
+        let mut sim = SimulatedMcp9808::new(SlaveAddress::Default);
+        sim.set_temperature(temp);
+        MCP9808::probe(sim, SlaveAddress::Default).unwrap()
+    }
+
+    #[test]
+    fn resolution() {
+        let mut dev = driver(Celsius::from_sixteenths(-163));
+        assert_eq!(dev.read_celsius().unwrap(), Celsius::from_sixteenths(-163));
+
+        dev.set_resolution(ResolutionVal::Deg_0_5C).unwrap();
+        assert_eq!(dev.read_celsius().unwrap(), Celsius::from_sixteenths(-168));
+        assert_eq!(dev.sync_resolution().unwrap(), ResolutionVal::Deg_0_5C);
+    }
+
+    #[test]
+    fn reserved_bits() {
+        let mut dev = driver(Celsius::ZERO);
+        let mut reg = reg_temp_alert_upper::new();
+        reg.register_mut().set_u16(0xffff);
+        dev.write_register(reg).unwrap();
+        assert_eq!(dev.read_alert_upper().unwrap().register().as_u16(), 0x1ffc);
+
+        let mut conf = reg_conf::new();
+        conf.register_mut().set_u16(0xf830);
+        dev.write_register(conf).unwrap();
+        assert_eq!(dev.read_configuration().unwrap().register().as_u16(), 0);
+
+        // writes to read-only registers are ignored
+        let mut ids = crate::reg::Register::new(0b0110, 2).unwrap();
+        ids.set_u16(0x1234);
+        dev.write_register(ids).unwrap();
+        assert!(dev.verify_identity().is_ok());
+    }
+
+    #[test]
+    fn locks() {
+        let mut dev = driver(Celsius::ZERO);
+        dev.set_alert_upper(Celsius::from_degrees(30)).unwrap();
+        dev.lock_window().unwrap();
+
+        // the driver refuses, the device would ignore it
+        let err = dev.set_alert_upper(Celsius::from_degrees(40)).err();
+        assert!(matches!(err, Some(Error::Locked(Lock::Window))));
+        let mut sim = dev.free();
+        let mut reg = reg_temp_alert_upper::new();
+        reg.register_mut().set_u16(0x0280);
+        let raw = reg.register();
+        RegisterInterface::write_register(&mut sim, 0x18, raw.get_ptr(), raw.get_buf()).unwrap();
+        assert_eq!(sim.register(PTR_UPPER), 0x01e0);
+
+        let mut conf = reg_conf::new();
+        conf.set_alert_mode(AlertMode::Interrupt);
+        conf.set_shutdown_mode(ShutdownMode::Shutdown);
+        let raw = conf.register();
+        RegisterInterface::write_register(&mut sim, 0x18, raw.get_ptr(), raw.get_buf()).unwrap();
+        assert_eq!(sim.register(PTR_CONFIG), BIT_WINDOW_LOCK);
+
+        sim.power_on_reset();
+        assert_eq!(sim.register(PTR_CONFIG), 0);
+    }
+
+    #[test]
+    fn interrupt() {
+        let mut dev = driver(Celsius::from_degrees(20));
+        dev.configure_alert_window(
+            Celsius::from_degrees(10),
+            Celsius::from_degrees(30),
+            Celsius::from_degrees(50),
+            Hysteresis::Deg_1_5C,
+        )
+        .unwrap();
+        // the new limits apply from the next conversion on
+        dev.i2c.tick();
+        let config = ConfigBuilder::from_register(&dev.read_configuration().unwrap())
+            .alert_mode(AlertMode::Interrupt)
+            .alert_control(AlertControl::Enabled);
+        dev.apply_config(&config).unwrap();
+
+        let mut sim = dev.free();
+        assert!(!sim.alert().asserted);
+        sim.set_temperature(Celsius::from_degrees(31));
+        assert!(sim.alert().asserted && !sim.alert().pin_high);
+
+        let mut dev = MCP9808::new(sim);
+        let event = dev.service_alert().unwrap();
+        assert!(event.upper && event.cleared);
+        assert_eq!(event.temperature, Celsius::from_degrees(31));
+        let sim = dev.free();
+        assert!(!sim.alert().asserted);
+    }
+
+    #[test]
+    fn shutdown() {
+        let mut dev = driver(Celsius::from_degrees(20));
+        let mut conf = dev.read_configuration().unwrap();
+        conf.set_shutdown_mode(ShutdownMode::Shutdown);
+        dev.write_register(conf).unwrap();
+
+        let mut sim = dev.free();
+        assert!(sim.is_shutdown());
+        sim.set_temperature(Celsius::from_degrees(22));
+
+        let sim = RefCell::new(sim);
+        let mut dev = MCP9808::new(Shared(&sim));
+        assert_eq!(dev.read_celsius().unwrap(), Celsius::from_degrees(20));
+        let temp = dev.measure_once(&mut Shared(&sim)).unwrap();
+        assert_eq!(temp, Celsius::from_degrees(22));
+        assert!(sim.borrow().is_shutdown());
+    }
+
+    #[test]
+    fn wake_on_change() {
+        let sim = RefCell::new(SimulatedMcp9808::new(SlaveAddress::Default));
+        sim.borrow_mut().set_temperature(Celsius::from_degrees(20));
+        let mut dev = MCP9808::new(Shared(&sim));
+        let mut delay = Shared(&sim);
+        let delta = Celsius::from_degrees(1);
+        dev.set_alert_critical(Celsius::from_degrees(50)).unwrap();
+
+        dev.enable_wake_on_change(delta, &mut delay).unwrap();
+        sim.borrow_mut().tick();
+        assert!(!sim.borrow().alert().asserted);
+
+        sim.borrow_mut()
+            .set_temperature(Celsius::from_millicelsius(21_500));
+        assert!(sim.borrow().alert().asserted);
+
+        // the conversion seeing the new window raises the interrupt before it is cleared
+        let window = dev.recenter_wake_on_change(delta, &mut delay).unwrap();
+        assert_eq!(window.upper, Celsius::from_millicelsius(22_500));
+        assert!(!sim.borrow().alert().asserted);
+        sim.borrow_mut().tick();
+        assert!(!sim.borrow().alert().asserted);
+    }
+}
@@ -0,0 +1,256 @@
+//! Whole register file read at once, for diagnostics
+
+use crate::celsius::Celsius;
+use crate::prelude::Read;
+use crate::reg_conf::{self, AlertStatus, ConfigBuilder, Configuration};
+use crate::reg_device_id::{self, DeviceId};
+use crate::reg_manuf_id::{self, ManufacturerId};
+use crate::reg_res::{self, Resolution, ResolutionVal};
+use crate::reg_temp::{self, Temperature};
+use crate::reg_temp_alert_crit;
+use crate::reg_temp_alert_lower;
+use crate::reg_temp_alert_upper;
+use crate::reg_temp_generic::ReadableTempRegister;
+use core::fmt;
+
+/// names of the registers, by pointer 1 to 8
+const NAMES: [&str; 8] = [
+    "CONFIG",
+    "TUPPER",
+    "TLOWER",
+    "TCRIT",
+    "TA",
+    "MANUF_ID",
+    "DEVICE_ID",
+    "RESOLUTION",
+];
+
+/// All registers, decoded, as read by `MCP9808::read_all()`.
+///
+/// `Display` prints a register dump with raw and decoded values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RegisterSnapshot {
+    /// configuration, including the lock bits
+    pub config: ConfigBuilder,
+    /// whether the ALERT output is asserted
+    pub alert_status: AlertStatus,
+    pub upper: Celsius,
+    pub lower: Celsius,
+    pub critical: Celsius,
+    /// ambient temperature, at `resolution`
+    pub temperature: Celsius,
+    /// TA ≥ tCRIT
+    pub above_critical: bool,
+    /// TA > tUPPER
+    pub above_upper: bool,
+    /// TA < tLOWER
+    pub below_lower: bool,
+    pub manufacturer_id: u16,
+    pub device_id: u8,
+    pub revision: u8,
+    pub resolution: ResolutionVal,
+    /// raw contents, by pointer 1 to 8
+    raw: [u16; 8],
+}
+
+/// Bus buffers of all registers.
+#[derive(Default)]
+pub(crate) struct RawRegisters([[u8; 2]; 8]);
+
+impl RawRegisters {
+    /// pointer of the register `batch()` ends with
+    pub(crate) const LAST_POINTER: u8 = 0b1000;
+
+    /// `(pointer, buffer)` of every register, sized to the register
+    pub(crate) fn batch(&mut self) -> [(u8, &mut [u8]); 8] {
+        let [conf, upper, lower, crit, temp, manuf, device, res] = self.0.each_mut();
+        let (res, _) = res.split_at_mut(1);
+        [
+            (0b0001, conf),
+            (0b0010, upper),
+            (0b0011, lower),
+            (0b0100, crit),
+            (0b0101, temp),
+            (0b0110, manuf),
+            (0b0111, device),
+            (0b1000, res),
+        ]
+    }
+
+    /// `fallback` decodes the temperature if the resolution register holds unimplemented bits
+    pub(crate) fn decode(&self, fallback: ResolutionVal) -> RegisterSnapshot {
+        let [conf_buf, upper_buf, lower_buf, crit_buf, temp_buf, manuf_buf, device_buf, res_buf] =
+            self.0;
+
+        let mut conf = reg_conf::new();
+        conf.register_mut().set_buf(conf_buf);
+        let mut upper = reg_temp_alert_upper::new();
+        upper.register_mut().set_buf(upper_buf);
+        let mut lower = reg_temp_alert_lower::new();
+        lower.register_mut().set_buf(lower_buf);
+        let mut crit = reg_temp_alert_crit::new();
+        crit.register_mut().set_buf(crit_buf);
+        let mut temp = reg_temp::new();
+        temp.register_mut().set_buf(temp_buf);
+        let mut manuf = reg_manuf_id::new();
+        manuf.register_mut().set_buf(manuf_buf);
+        let mut device = reg_device_id::new();
+        device.register_mut().set_buf(device_buf);
+        let mut res = reg_res::new();
+        res.register_mut().set_buf(res_buf);
+
+        let resolution = res.get_resolution().unwrap_or(fallback);
+        let mut raw = self.0.map(u16::from_be_bytes);
+        if let Some(res) = raw.last_mut() {
+            *res >>= 8;
+        }
+        RegisterSnapshot {
+            config: ConfigBuilder::from_register(&conf),
+            alert_status: conf.get_alert_status(),
+            upper: upper.get_celsius(ResolutionVal::Deg_0_25C),
+            lower: lower.get_celsius(ResolutionVal::Deg_0_25C),
+            critical: crit.get_celsius(ResolutionVal::Deg_0_25C),
+            temperature: temp.get_celsius(resolution),
+            above_critical: temp.is_alert_critical(),
+            above_upper: temp.is_alert_upper(),
+            below_lower: temp.is_alert_lower(),
+            manufacturer_id: manuf.get_manufacturer_id(),
+            device_id: device.get_device_id(),
+            revision: device.get_device_rev(),
+            resolution,
+            raw,
+        }
+    }
+}
+
+impl RegisterSnapshot {
+    /// raw contents of the register at `ptr`
+    pub fn raw(&self, ptr: u8) -> Option<u16> {
+        self.raw.get(usize::from(ptr).checked_sub(1)?).copied()
+    }
+}
+
+/// `25.2500 °C`, exact to 1/16 °C
+struct Temp(Celsius);
+
+impl fmt::Display for Temp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let micro = self.0.to_microcelsius();
+        let sign = if micro < 0 { "-" } else { "" };
+        let micro = micro.unsigned_abs();
+        write!(
+            f,
+            "{}{}.{:04} °C",
+            sign,
+            micro / 1_000_000,
+            micro % 1_000_000 / 100
+        )
+    }
+}
+
+impl fmt::Display for RegisterSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let conf = self.config.build();
+        for (ptr, (name, raw)) in (1u8..).zip(NAMES.iter().zip(self.raw)) {
+            if ptr == 0b1000 {
+                write!(f, "{:<10} {:#04x}    ", name, raw)?;
+            } else {
+                write!(f, "{:<10} {:#06x}  ", name, raw)?;
+            }
+            match ptr {
+                0b0001 => write!(
+                    f,
+                    "{:?} {:?}, select {:?}, output {:?} {:?}, window {:?}, critical {:?}, {:?}, \
+                     hysteresis {}",
+                    conf.get_alert_mode(),
+                    conf.get_alert_polarity(),
+                    conf.get_alert_select(),
+                    conf.get_alert_control(),
+                    self.alert_status,
+                    conf.get_window_lock(),
+                    conf.get_critical_lock(),
+                    conf.get_shutdown_mode(),
+                    Temp(conf.get_hysteresis().to_celsius()),
+                )?,
+                0b0010 => write!(f, "{}", Temp(self.upper))?,
+                0b0011 => write!(f, "{}", Temp(self.lower))?,
+                0b0100 => write!(f, "{}", Temp(self.critical))?,
+                0b0101 => {
+                    write!(f, "{}", Temp(self.temperature))?;
+                    for (set, flag) in [
+                        (self.above_critical, "TA >= TCRIT"),
+                        (self.above_upper, "TA > TUPPER"),
+                        (self.below_lower, "TA < TLOWER"),
+                    ] {
+                        if set {
+                            write!(f, ", {}", flag)?;
+                        }
+                    }
+                }
+                0b0110 => write!(f, "{:#06x}", self.manufacturer_id)?,
+                0b0111 => write!(f, "id {:#04x}, revision {}", self.device_id, self.revision)?,
+                _ => write!(f, "{:?}", self.resolution)?,
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::SlaveAddress;
+    use crate::reg_conf::{AlertControl, Hysteresis, WindowLock};
+    use crate::sim::SimulatedMcp9808;
+    use crate::MCP9808;
+
+    #[test]
+    fn read_all() {
+        let mut sim = SimulatedMcp9808::new(SlaveAddress::Default);
+        sim.set_temperature(Celsius::from_millicelsius(31_250));
+        let mut dev = MCP9808::new(sim);
+        dev.apply_config(
+            &ConfigBuilder::new()
+                .alert_control(AlertControl::Enabled)
+                .hysteresis(Hysteresis::Deg_1_5C),
+        )
+        .unwrap();
+        dev.set_alert_upper(Celsius::from_degrees(30)).unwrap();
+        dev.set_alert_critical(Celsius::from_millicelsius(-2_500))
+            .unwrap();
+        dev.set_resolution(ResolutionVal::Deg_0_25C).unwrap();
+        dev.lock_window().unwrap();
+        dev.i2c.tick();
+
+        let snapshot = dev.read_all().unwrap();
+        assert_eq!(snapshot.temperature, Celsius::from_millicelsius(31_250));
+        assert!(snapshot.above_critical && snapshot.above_upper && !snapshot.below_lower);
+        assert_eq!(snapshot.upper, Celsius::from_degrees(30));
+        assert_eq!(snapshot.raw(0b0010), Some(0x01e0));
+        assert_eq!(snapshot.raw(0b1000), Some(0b01));
+        assert_eq!(snapshot.raw(0), None);
+        assert_eq!(snapshot.resolution, ResolutionVal::Deg_0_25C);
+        assert_eq!(
+            snapshot.config,
+            ConfigBuilder::new()
+                .alert_control(AlertControl::Enabled)
+                .hysteresis(Hysteresis::Deg_1_5C)
+                .window_lock(WindowLock::Locked)
+        );
+
+        let dump = format!("{}", snapshot);
+        let expected = "\
+CONFIG     0x0258  Comparator ActiveLow, select All, output Enabled Asserted, window Locked, \
+critical Unlocked, Continuous, hysteresis 1.5000 °C
+TUPPER     0x01e0  30.0000 °C
+TLOWER     0x0000  0.0000 °C
+TCRIT      0x1fd8  -2.5000 °C
+TA         0xc1f4  31.2500 °C, TA >= TCRIT, TA > TUPPER
+MANUF_ID   0x0054  0x0054
+DEVICE_ID  0x0400  id 0x04, revision 0
+RESOLUTION 0x01    Deg_0_25C
+";
+        assert_eq!(dump, expected);
+    }
+}